@@ -1,17 +1,310 @@
-use spacetimedb::{Identity, ReducerContext, Table, TimeDuration};
-use spacetimedb::rand::Rng;
+use std::collections::HashMap;
+
+use spacetimedb::{Identity, ReducerContext, SpacetimeType, Table, TimeDuration, Timestamp};
+use spacetimedb::rand::rngs::StdRng;
+use spacetimedb::rand::{Rng, SeedableRng};
 
 const WORLD_WIDTH: f32 = 3000.0;
 const WORLD_HEIGHT: f32 = 3000.0;
 const MAX_FOOD: u32 = 200;
+/// Smallest world dimension `set_config` accepts; keeps spawn ranges non-empty.
+const MIN_WORLD_SIZE: u32 = 500;
+/// Bounds on the side length auto-resize may pick.
+const MIN_AUTO_WORLD_SIZE: f32 = 1500.0;
+const MAX_AUTO_WORLD_SIZE: f32 = 8000.0;
+/// World area auto-resize allots each live player (3000x3000 fits ten).
+const WORLD_AREA_PER_PLAYER: f32 = 900_000.0;
+/// Auto-resize ignores size changes smaller than this fraction.
+const WORLD_RESIZE_THRESHOLD: f32 = 0.05;
+/// Hex identities seeded into the `admin` table on init.
+const ADMIN_IDENTITIES: &[&str] = &[];
+/// Default `GameConfig.food_radius`.
 const FOOD_RADIUS: f32 = 6.0;
+const FOOD_MASS: f32 = 1.0;
+/// Rare high-value pellets; drawn larger so players can spot them.
+const GOLDEN_FOOD_MASS: f32 = 5.0;
+const GOLDEN_FOOD_RADIUS: f32 = 10.0;
+/// Under `FoodReplacement::Burst`, `maintain_food` waits until food falls
+/// below this fraction of `max_food` before refilling.
+const FOOD_BURST_THRESHOLD: f32 = 0.5;
+/// Probability that a newly spawned pellet is golden.
+const GOLDEN_FOOD_CHANCE: f64 = 0.05;
+/// `PlayerStats.food_eaten` needed for `AchievementKind::Glutton`.
+const GLUTTON_FOOD_EATEN: u64 = 100;
+/// Total mass needed for `AchievementKind::Titan`.
+const TITAN_MASS: f32 = 5000.0;
+/// Hotspots seeded on init for `FoodDistribution::Clustered`.
+const FOOD_CLUSTER_COUNT: u32 = 6;
+/// Standard deviation of a clustered pellet's offset from its hotspot.
+const FOOD_CLUSTER_SPREAD: f32 = 150.0;
 const INITIAL_MASS: f32 = 100.0;
+/// Eating rule: the single colliding cell must be this many times heavier than
+/// everything it would absorb. Covering a player's main cell absorbs the whole
+/// player (split cells included), so that is compared against their total mass;
+/// eating one stray split cell only needs to beat that cell. The eater's other
+/// cells never count.
+const EAT_MASS_RATIO: f32 = 1.1;
+/// Default `GameConfig.eat_cover_factor`.
+const EAT_COVER_FACTOR: f32 = 0.5;
+/// Most golden pellets one `scatter_food` drop makes; past that each pellet
+/// carries more mass.
+const KILL_SCATTER_MAX: u32 = 20;
+/// Minimum mass shed per eject; larger cells shed `EJECT_MASS_FRACTION` instead.
 const EJECT_MASS_AMOUNT: f32 = 10.0;
+const EJECT_MASS_FRACTION: f32 = 0.02;
+/// Backward speed (units/sec) added to the main cell per eject; coasts ~12 units.
+const EJECT_IMPULSE: f32 = 60.0;
+/// Cap on accumulated eject impulse, so rapid ejects can't build unbounded speed.
+const MAX_EJECT_IMPULSE: f32 = 240.0;
+/// Default `GameConfig.eject_distance`: 15 radii is ~300 units at `INITIAL_MASS`.
+const EJECT_DISTANCE: f32 = 15.0;
+/// Default `GameConfig.eject_spread`: up to ~6 degrees either side of the aim.
+const EJECT_SPREAD: f32 = 0.1;
+/// Default size curve, `radius = 2 * sqrt(mass)`; see `GameConfig::mass_to_radius`.
+const RADIUS_SCALE: f32 = 2.0;
+const RADIUS_EXPONENT: f32 = 0.5;
 const MIN_SPLIT_MASS: f32 = 200.0;
+/// Default `GameConfig.merge_delay_micros`.
+const MERGE_DELAY_MICROS: i64 = 10_000_000;
+/// Default `GameConfig.min_cell_mass`.
+const MIN_CELL_MASS: f32 = 50.0;
+/// Maximum number of cells (main + split halves) a single player may own.
+const MAX_CELLS: usize = 16;
 const MASS_DECAY_RATE: f32 = 0.998;
+const DECAY_INTERVAL_MICROS: i64 = 2_000_000;
+/// Shortest decay interval `set_decay` accepts.
+const MIN_DECAY_INTERVAL_MICROS: i64 = 100_000;
+const MAX_MASS: f32 = 10_000.0;
+const DECAY_MASS_EXPONENT: f32 = 0.5;
+/// Upper bound on the fraction of mass any cell loses in one decay tick.
+const MAX_DECAY_LOSS: f32 = 0.05;
+/// Most ejected pellets in the world at once; the oldest is evicted past this.
+const MAX_EJECTED: u64 = 500;
+/// Uneaten ejected mass is removed after this long.
+const EJECTED_MASS_LIFETIME_MICROS: i64 = 30_000_000;
+/// Kill feed entries are trimmed after this long.
+const KILL_EVENT_LIFETIME_MICROS: i64 = 30_000_000;
+/// `game_event` rows kept; the oldest beyond this are trimmed.
+const MAX_GAME_EVENTS: usize = 1000;
+/// `mass_audit` rows kept (an hour at one audit every 10 seconds).
+const MAX_MASS_AUDITS: usize = 360;
+const MAX_CHAT_LEN: usize = 200;
+const CHAT_MESSAGE_LIFETIME_MICROS: i64 = 60_000_000;
+/// Exponential velocity decay (per second) for ejected mass in flight.
+const EJECT_FRICTION: f32 = 5.0;
+/// Ejected mass slower than this (units/sec) comes to rest.
+const EJECT_REST_SPEED: f32 = 5.0;
+const MOTION_TICK_MICROS: i64 = 50_000;
+const LEADERBOARD_SIZE: usize = 10;
+const BOT_COUNT: u32 = 5;
+/// Default `GameConfig.max_players`.
+const MAX_PLAYERS: u32 = 100;
+/// How far a bot looks for food and threats.
+const BOT_SIGHT_RADIUS: f32 = 400.0;
+const BOT_TICK_MICROS: i64 = 200_000;
+/// Players under this total mass pull nearby food toward them (if `food_magnet` is on).
+const FOOD_MAGNET_MASS: f32 = 200.0;
+/// How far beyond a small player's edge the magnet reaches.
+const FOOD_MAGNET_RANGE: f32 = 60.0;
+/// Distance a pellet drifts toward its player per magnet tick.
+const FOOD_MAGNET_STEP: f32 = 4.0;
+const FOOD_MAGNET_TICK_MICROS: i64 = 100_000;
+const COLLISION_TICK_MICROS: i64 = 100_000;
+/// World units per minimap grid square.
+const MINIMAP_CELL_SIZE: f32 = 100.0;
+const MINIMAP_TICK_MICROS: i64 = 250_000;
+const METRICS_TICK_MICROS: i64 = 5_000_000;
+const ROUND_DURATION_MICROS: i64 = 300_000_000;
+const VIRUS_COUNT: u32 = 12;
+const VIRUS_MASS: f32 = 150.0;
+/// Number of cells (including the main cell) a player bursts into on a virus.
+const VIRUS_SPLIT_PIECES: usize = 8;
+/// No virus fragment is made lighter than this.
+const MIN_FRAGMENT_MASS: f32 = 20.0;
+/// Ejected pellets a virus absorbs before it shoots off a new virus.
+const VIRUS_FEED_LIMIT: u32 = 7;
+const VIRUS_SHOOT_DISTANCE: f32 = 300.0;
+/// Power-ups kept on the map; topped up by `respawn_powerups`.
+const POWERUP_COUNT: u32 = 4;
+const POWERUP_RADIUS: f32 = 12.0;
+const BOOST_DURATION_MICROS: i64 = 5_000_000;
+const SHIELD_DURATION_MICROS: i64 = 8_000_000;
+/// Movement speed multiplier while a speed boost is active.
+const BOOST_SPEED_MULTIPLIER: f32 = 1.5;
+/// Portal pairs placed at random on init.
+const PORTAL_COUNT: u32 = 2;
+const PORTAL_RADIUS: f32 = 40.0;
+/// After teleporting a player can't use any portal for this long, so landing
+/// on the far end doesn't bounce them straight back.
+const TELEPORT_COOLDOWN_MICROS: i64 = 3_000_000;
+/// Walls placed at random on init.
+const OBSTACLE_COUNT: u32 = 4;
+const OBSTACLE_MIN_SIDE: f32 = 60.0;
+const OBSTACLE_MAX_SIDE: f32 = 240.0;
+/// Colors seeded into `color_palette` on init.
+const DEFAULT_PLAYER_COLORS: &[u32] = &[
+    0x4a90d9, 0xe74c3c, 0x2ecc71, 0xf39c12,
+    0x9b59b6, 0x1abc9c, 0xe91e63, 0x00bcd4,
+];
+const MAX_NAME_LEN: usize = 16;
+const DEFAULT_NAME: &str = "Anonymous";
+/// How long a disconnected player's cells linger so they can reconnect.
+const RECONNECT_GRACE_MICROS: i64 = 10_000_000;
+/// While paused, one-shot schedules (round end, merges, disconnect expiry)
+/// re-queue themselves this far ahead instead of firing.
+const PAUSED_RETRY_MICROS: i64 = 1_000_000;
+/// Default `GameConfig.idle_timeout_micros`.
+const IDLE_TIMEOUT_MICROS: i64 = 120_000_000;
+/// Random positions `spawn` considers before picking the safest.
+const SPAWN_CANDIDATES: usize = 8;
+/// Score for a kill, on top of the victim's own score.
+const KILL_SCORE: u64 = 10;
+/// Newly spawned players cannot be eaten for this long.
+const SPAWN_PROTECTION_MICROS: i64 = 5_000_000;
+/// Fraction of mass drained each second from cells outside the safe zone.
+const ZONE_DAMAGE_RATE: f32 = 0.05;
+/// Cells drained below this mass are eliminated.
+const ZONE_MIN_MASS: f32 = 10.0;
+const EJECT_COOLDOWN_MICROS: i64 = 200_000;
+/// How long a player must wait before eating mass they ejected themselves.
+const SELF_EAT_DELAY_MICROS: i64 = 1_000_000;
+const SPLIT_COOLDOWN_MICROS: i64 = 500_000;
+/// How far a player at `initial_mass` can see; see `view_radius`.
+const BASE_VIEW_RADIUS: f32 = 800.0;
+/// Center-to-center distance within which `refresh_threats` reports players
+/// big enough to eat the caller.
+const THREAT_RADIUS: f32 = 400.0;
+/// Movement speed (units/sec) of a cell at `initial_mass`; matches the client.
+const BASE_SPEED: f32 = 150.0;
+/// Slack over the nominal speed to absorb network jitter between updates.
+const MOVE_SPEED_TOLERANCE: f32 = 1.5;
+/// Longest gap between position updates that still earns movement budget.
+const MAX_MOVE_ELAPSED_SECS: f32 = 0.25;
+/// Side length of one spatial-index bucket; see `GridCell`.
+const GRID_CELL_SIZE: f32 = 200.0;
+
+/// Client-supplied floats must pass this before touching any position math.
+fn all_finite(values: &[f32]) -> bool {
+    values.iter().all(|v| v.is_finite())
+}
+
+/// Grid coordinate of the bucket containing world coordinate `v`.
+fn grid_coord(v: f32) -> u32 {
+    (v / GRID_CELL_SIZE).floor().max(0.0) as u32
+}
+
+/// Packed `(grid_x, grid_y)` key for the bucket containing `(x, y)`.
+fn grid_bucket(x: f32, y: f32) -> u64 {
+    bucket_key(grid_coord(x), grid_coord(y))
+}
+
+fn bucket_key(grid_x: u32, grid_y: u32) -> u64 {
+    ((grid_x as u64) << 32) | grid_y as u64
+}
+
+/// Movement speed (units/sec) for a cell of the given mass.
+///
+/// Speed falls off as `mass^-0.4`, normalised so a cell at the configured
+/// `initial_mass` moves at `BASE_SPEED`: doubling mass costs ~24% speed, and a
+/// cell 20x heavier moves at roughly 30% of a fresh one. The curve is gentler
+/// than the client's `1/sqrt` so honest clients never hit the server cap.
+fn cell_speed(cfg: &GameConfig, mass: f32) -> f32 {
+    BASE_SPEED * (cfg.initial_mass / mass.max(1.0)).powf(0.4)
+}
+
+/// How far a player with `total_mass` can see: `BASE_VIEW_RADIUS * (mass /
+/// initial_mass)^0.25`, never below `BASE_VIEW_RADIUS`. A 16x heavier player
+/// sees twice as far. Non-decreasing in mass, so clients can zoom out smoothly.
+fn view_radius(cfg: &GameConfig, total_mass: f32) -> f32 {
+    BASE_VIEW_RADIUS * (total_mass / cfg.initial_mass).max(1.0).powf(0.25)
+}
+
+/// Move from `(from_x, from_y)` toward `(to_x, to_y)`, travelling at most `max_dist`.
+fn capped_move(from_x: f32, from_y: f32, to_x: f32, to_y: f32, max_dist: f32) -> (f32, f32) {
+    let dx = to_x - from_x;
+    let dy = to_y - from_y;
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist <= max_dist {
+        return (to_x, to_y);
+    }
+    let scale = max_dist / dist;
+    (from_x + dx * scale, from_y + dy * scale)
+}
+
+/// Mass left after one decay tick. The fraction lost is `1 - decay_rate` at
+/// `initial_mass` and scales with `(mass / initial_mass)^decay_mass_exponent`, so
+/// giants shrink proportionally faster; an exponent of 0 gives flat decay.
+fn decayed_mass(cfg: &GameConfig, mass: f32) -> f32 {
+    let scale = (mass / cfg.initial_mass).max(0.0).powf(cfg.decay_mass_exponent);
+    let loss = ((1.0 - cfg.decay_rate) * scale).min(MAX_DECAY_LOSS);
+    mass * (1.0 - loss)
+}
+
+/// Whether `(to_x, to_y)` lies within `reach` of `(from_x, from_y)`, boundary
+/// included. This is the distance test every eat path uses.
+fn within_reach(from_x: f32, from_y: f32, to_x: f32, to_y: f32, reach: f32) -> bool {
+    let dx = to_x - from_x;
+    let dy = to_y - from_y;
+    dx * dx + dy * dy <= reach * reach
+}
+
+/// Distance a cell may have travelled since `last_move_micros`.
+fn max_move_dist(ctx: &ReducerContext, cfg: &GameConfig, mass: f32, last_move_micros: i64) -> f32 {
+    move_budget(cfg, mass, last_move_micros, ctx.timestamp.to_micros_since_unix_epoch())
+}
+
+/// Distance a cell of `mass` may travel between `last_move_micros` and `now_micros`.
+/// Clock skew counts as no time; gaps longer than `MAX_MOVE_ELAPSED_SECS` are capped.
+fn move_budget(cfg: &GameConfig, mass: f32, last_move_micros: i64, now_micros: i64) -> f32 {
+    let elapsed_micros = now_micros - last_move_micros;
+    let elapsed_secs = (elapsed_micros.max(0) as f32 / 1_000_000.0).min(MAX_MOVE_ELAPSED_SECS);
+    cell_speed(cfg, mass) * MOVE_SPEED_TOLERANCE * elapsed_secs
+}
+
+/// One entry of an `update_all_positions` batch; `cell_id` 0 is the main cell.
+#[derive(SpacetimeType, Clone, Copy, Debug)]
+pub struct CellMove {
+    pub cell_id: u64,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Where `spawn_food_pellet` places new pellets.
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoodDistribution {
+    /// Uniformly over the world.
+    Uniform,
+    /// Gaussian scatter around the `food_cluster` hotspots.
+    Clustered,
+}
+
+/// How eaten food comes back.
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoodReplacement {
+    /// `maintain_food` tops the world back up to `max_food` every second.
+    TopUp,
+    /// Each eaten pellet is replaced on the spot, one for one.
+    Immediate,
+    /// Nothing comes back until food drops below `FOOD_BURST_THRESHOLD` of
+    /// `max_food`, then `maintain_food` refills it all at once.
+    Burst,
+}
+
+/// How much overlap a bigger cell needs before it eats a smaller one.
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EatOverlapMode {
+    /// Edges touching is enough.
+    EdgeTouch,
+    /// The eater must cover the target's center by `eat_cover_factor` of the
+    /// target's radius: `dist < eater.radius - target.radius * k`.
+    CenterCover,
+}
 
-fn mass_to_radius(mass: f32) -> f32 {
-    mass.sqrt() * 2.0
+/// Playable area: the full rectangle, or the disc inscribed in it.
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorldShape {
+    Rectangle,
+    Circle,
 }
 
 #[spacetimedb::table(name = "game_config", accessor = game_config, public)]
@@ -21,6 +314,139 @@ pub struct GameConfig {
     pub max_food: u32,
     pub world_width: u32,
     pub world_height: u32,
+    /// Ceiling for any single cell's mass.
+    pub max_mass: f32,
+    /// Number of server-driven bot players kept in the world.
+    pub bot_count: u32,
+    /// Most connected players (bots included) at once; further spawns are refused.
+    pub max_players: u32,
+    pub world_shape: WorldShape,
+    /// How steeply decay grows with mass; see `decayed_mass`.
+    pub decay_mass_exponent: f32,
+    /// Time between decay ticks.
+    pub decay_interval_micros: i64,
+    /// Fraction of mass kept per tick at `initial_mass`; 1.0 disables decay.
+    pub decay_rate: f32,
+    /// How far ejected mass travels from the cell's center, in multiples of its radius.
+    pub eject_distance: f32,
+    /// Largest random deviation (radians) of an eject from the aimed direction,
+    /// so held-down ejects fan out instead of stacking; 0 fires dead straight.
+    pub eject_spread: f32,
+    /// Scale the world (and food target) with the live player count; see `resize_world`.
+    /// Off by default, since while on it overrides the size and food target
+    /// given to `set_config`.
+    pub auto_resize: bool,
+    /// Despawn connected players idle this long (see `reap_idle`); 0 disables.
+    pub idle_timeout_micros: i64,
+    /// Permadeath: players eaten by another player stay dead until the next round.
+    pub hardcore: bool,
+    /// Let players under `FOOD_MAGNET_MASS` attract nearby food; see `attract_food`.
+    pub food_magnet: bool,
+    /// While set, movement, eating, ejecting, splitting and the simulation ticks do nothing.
+    pub paused: bool,
+    pub food_distribution: FoodDistribution,
+    pub food_replacement: FoodReplacement,
+    /// Mass of a freshly spawned player; also the floor for decay and ejecting.
+    pub initial_mass: f32,
+    /// Cells lighter than this cannot split.
+    pub min_split_mass: f32,
+    /// Base time a split cell must wait before it can merge back.
+    pub merge_delay_micros: i64,
+    /// Extra merge wait per unit of the split cell's mass; 0 gives a flat delay.
+    pub merge_delay_per_mass_micros: f32,
+    /// Lightest half a split may produce; cells whose halves would fall below
+    /// this stay whole even above `min_split_mass`.
+    pub min_cell_mass: f32,
+    /// Smallest pellet `eject_mass` will shed.
+    pub eject_mass_amount: f32,
+    /// Size curve: a cell of mass `m` has radius `radius_scale * m^radius_exponent`.
+    pub radius_scale: f32,
+    pub radius_exponent: f32,
+    /// Radius of an ordinary (non-golden) food pellet.
+    pub food_radius: f32,
+    /// Fraction of a victim's total mass the eater gains; the rest is lost.
+    pub absorb_ratio: f32,
+    /// Drop mass lost to `absorb_ratio` as golden pellets around the kill site.
+    pub scatter_lost_mass: bool,
+    pub eat_overlap_mode: EatOverlapMode,
+    /// `k` in the `CenterCover` rule; 0 only needs the center inside the eater.
+    pub eat_cover_factor: f32,
+    /// When set, food, hotspot and spawn placement follow a fixed sequence
+    /// derived from this seed; see `game_rng`.
+    pub rng_seed: Option<u64>,
+}
+
+impl GameConfig {
+    fn width(&self) -> f32 {
+        self.world_width as f32
+    }
+
+    fn height(&self) -> f32 {
+        self.world_height as f32
+    }
+
+    /// Radius of a cell (or virus, or ejected pellet) of `mass`.
+    fn mass_to_radius(&self, mass: f32) -> f32 {
+        self.radius_scale * mass.max(0.0).powf(self.radius_exponent)
+    }
+
+    /// Whether a cell of `mass` may split into two viable halves.
+    fn can_split(&self, mass: f32) -> bool {
+        mass >= self.min_split_mass && mass / 2.0 >= self.min_cell_mass
+    }
+
+    /// Farthest center-to-center distance at which a cell of `eater_radius`
+    /// can eat one of `target_radius`, per `eat_overlap_mode`.
+    fn eat_reach(&self, eater_radius: f32, target_radius: f32) -> f32 {
+        match self.eat_overlap_mode {
+            EatOverlapMode::EdgeTouch => eater_radius + target_radius,
+            EatOverlapMode::CenterCover => (eater_radius - target_radius * self.eat_cover_factor).max(0.0),
+        }
+    }
+
+    /// How long a freshly split cell of `mass` waits before it may merge.
+    fn merge_delay(&self, mass: f32) -> TimeDuration {
+        let extra = (self.merge_delay_per_mass_micros * mass.max(0.0)) as i64;
+        TimeDuration::from_micros(self.merge_delay_micros + extra)
+    }
+
+    /// Largest radius any food pellet can have, for reach checks.
+    fn max_food_radius(&self) -> f32 {
+        self.food_radius.max(GOLDEN_FOOD_RADIUS)
+    }
+
+    /// Pull `(x, y)` back inside the world, keeping `margin` clear of the edge.
+    /// In `Circle` mode points outside the disc are projected onto its rim.
+    fn clamp_to_world(&self, x: f32, y: f32, margin: f32) -> (f32, f32) {
+        match self.world_shape {
+            WorldShape::Rectangle => (
+                x.clamp(margin, self.width() - margin),
+                y.clamp(margin, self.height() - margin),
+            ),
+            WorldShape::Circle => {
+                let (cx, cy) = (self.width() / 2.0, self.height() / 2.0);
+                let max_r = (cx.min(cy) - margin).max(0.0);
+                let (dx, dy) = (x - cx, y - cy);
+                let dist = (dx * dx + dy * dy).sqrt();
+                if dist <= max_r {
+                    (x, y)
+                } else {
+                    (cx + dx / dist * max_r, cy + dy / dist * max_r)
+                }
+            }
+        }
+    }
+
+    /// Uniformly random point inside the world, at least `margin` from the edge.
+    fn random_position(&self, rng: &mut impl Rng, margin: f32) -> (f32, f32) {
+        loop {
+            let x = rng.gen_range(margin..(self.width() - margin));
+            let y = rng.gen_range(margin..(self.height() - margin));
+            if self.clamp_to_world(x, y, margin) == (x, y) {
+                return (x, y);
+            }
+        }
+    }
 }
 
 /// Main player row: identity, name, position, mass, color.
@@ -34,6 +460,36 @@ pub struct Player {
     pub radius: f32,
     pub mass: f32,
     pub color: u32,
+    pub last_move_micros: i64,
+    pub spawned_at: Timestamp,
+    /// Cannot be eaten before this time; cleared early once the player eats someone.
+    pub spawn_protected_until: Timestamp,
+    /// Mirrors spawn protection for the client to render; cleared when it ends.
+    pub invulnerable: bool,
+    /// `None` in free-for-all; teammates share an id.
+    pub team_id: Option<u32>,
+    /// Set while the client is gone; the row is deleted once the grace period lapses.
+    pub disconnected_at: Option<Timestamp>,
+    /// Start of the current connected stretch. Playtime since then is credited
+    /// to `PlayerStats` on disconnect or removal; reconnecting restarts it.
+    pub online_since: Timestamp,
+    /// Last move, eat, eject or split; `reap_idle` despawns players idle too long.
+    pub last_active_at: Timestamp,
+    /// Moves at `BOOST_SPEED_MULTIPLIER` speed until this time.
+    pub boost_until: Timestamp,
+    /// Cannot be eaten until this time.
+    pub shield_until: Timestamp,
+    /// Portals ignore this player until this time.
+    pub teleport_ready_at: Timestamp,
+    /// Points earned this life: one per unit of food mass, plus `KILL_SCORE` and
+    /// the victim's score per kill. Never decays.
+    pub score: u64,
+    /// `view_radius` of the player's total mass, for client zoom and view culling.
+    pub view_radius: f32,
+    /// Eject self-propulsion in units/sec, at most `MAX_EJECT_IMPULSE`; decays
+    /// to zero under `EJECT_FRICTION`.
+    pub vx: f32,
+    pub vy: f32,
 }
 
 /// Each split half is a separate row so both halves can move independently.
@@ -42,11 +498,20 @@ pub struct PlayerCell {
     #[primary_key]
     #[auto_inc]
     pub cell_id: u64,
+    #[index(btree)]
     pub player_identity: Identity,
     pub x: f32,
     pub y: f32,
     pub radius: f32,
     pub mass: f32,
+    pub last_move_micros: i64,
+    /// When this cell becomes eligible to merge back into the main cell.
+    pub merge_at: Timestamp,
+    /// Split launch velocity in units/sec; decays to zero under `EJECT_FRICTION`.
+    pub vx: f32,
+    pub vy: f32,
+    /// The owner's color when the cell split off, so loose cells render as theirs.
+    pub color: u32,
 }
 
 #[spacetimedb::table(name = "food_pellet", accessor = food_pellet, public)]
@@ -56,7 +521,10 @@ pub struct FoodPellet {
     pub id: u64,
     pub x: f32,
     pub y: f32,
+    /// Larger for higher-value pellets, so clients can style by size.
     pub radius: f32,
+    /// Mass gained by eating this pellet.
+    pub mass: f32,
 }
 
 /// Ejected mass pellets visible to all players.
@@ -69,457 +537,6045 @@ pub struct EjectedMass {
     pub y: f32,
     pub radius: f32,
     pub mass: f32,
+    pub ejected_at: Timestamp,
+    /// Velocity in units/sec; decays to zero under `EJECT_FRICTION`.
+    pub vx: f32,
+    pub vy: f32,
+    /// The ejecting player's color.
+    pub color: u32,
+    /// Who ejected it; they cannot re-eat it for `SELF_EAT_DELAY_MICROS`.
+    pub ejector: Identity,
 }
 
-/// Drives mass decay every 2 seconds (repeating schedule).
-#[spacetimedb::table(name = "mass_decay_schedule", accessor = mass_decay_schedule, scheduled(decay_mass))]
-pub struct MassDecaySchedule {
-    #[primary_key]
-    #[auto_inc]
-    pub scheduled_id: u64,
-    pub scheduled_at: spacetimedb::ScheduleAt,
-}
-
-/// One-time schedule to merge a split cell back after 10 seconds.
-#[spacetimedb::table(name = "split_merge_schedule", accessor = split_merge_schedule, scheduled(merge_split))]
-pub struct SplitMergeSchedule {
+/// Lifetime stats per identity. Survives despawns; cleared only by `reset_stats`.
+#[spacetimedb::table(name = "player_stats", accessor = player_stats, public)]
+pub struct PlayerStats {
     #[primary_key]
-    #[auto_inc]
-    pub scheduled_id: u64,
-    pub scheduled_at: spacetimedb::ScheduleAt,
-    pub player_identity: Identity,
+    pub identity: Identity,
+    pub food_eaten: u64,
+    /// Kills: other players this identity has eaten.
+    pub players_eaten: u64,
+    pub times_split: u64,
+    pub mass_ejected: f32,
+    pub max_mass_reached: f32,
+    pub total_playtime_micros: u64,
+    /// Times this identity was eaten by another player.
+    pub deaths: u64,
 }
 
-// ---------------------------------------------------------------------------
-// Lifecycle
-// ---------------------------------------------------------------------------
-
-#[spacetimedb::reducer(init)]
-pub fn init(ctx: &ReducerContext) {
-    ctx.db.game_config().insert(GameConfig {
-        id: 0,
-        max_food: MAX_FOOD,
-        world_width: WORLD_WIDTH as u32,
-        world_height: WORLD_HEIGHT as u32,
-    });
-
-    let mut rng = ctx.rng();
-    for _ in 0..MAX_FOOD {
-        let x = rng.gen_range(20.0_f32..(WORLD_WIDTH - 20.0));
-        let y = rng.gen_range(20.0_f32..(WORLD_HEIGHT - 20.0));
-        ctx.db.food_pellet().insert(FoodPellet { id: 0, x, y, radius: FOOD_RADIUS });
+impl PlayerStats {
+    /// Kills per death; with no deaths yet this is just the kill count.
+    pub fn kd_ratio(&self) -> f32 {
+        self.players_eaten as f32 / self.deaths.max(1) as f32
     }
 
-    // Start the repeating mass-decay schedule
-    let two_secs = TimeDuration::from_micros(2_000_000);
-    ctx.db.mass_decay_schedule().insert(MassDecaySchedule {
-        scheduled_id: 0,
-        scheduled_at: two_secs.into(),
-    });
+    /// Every achievement these stats qualify for, already unlocked or not.
+    fn achievements(&self) -> Vec<AchievementKind> {
+        [
+            (AchievementKind::FirstBlood, self.players_eaten >= 1),
+            (AchievementKind::Glutton, self.food_eaten >= GLUTTON_FOOD_EATEN),
+            (AchievementKind::Titan, self.max_mass_reached >= TITAN_MASS),
+        ]
+        .into_iter()
+        .filter(|&(_, reached)| reached)
+        .map(|(kind, _)| kind)
+        .collect()
+    }
 }
 
-#[spacetimedb::reducer(client_connected)]
-pub fn identity_connected(_ctx: &ReducerContext) {}
-
-#[spacetimedb::reducer(client_disconnected)]
-pub fn identity_disconnected(ctx: &ReducerContext) {
-    let identity = ctx.sender();
-    ctx.db.player().identity().delete(identity);
-    delete_player_cells(ctx, identity);
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AchievementKind {
+    /// First player eaten.
+    FirstBlood,
+    /// `GLUTTON_FOOD_EATEN` pellets eaten.
+    Glutton,
+    /// `TITAN_MASS` total mass reached.
+    Titan,
 }
 
-// ---------------------------------------------------------------------------
-// Player management
-// ---------------------------------------------------------------------------
+/// Unlocked achievements, at most one row per `(identity, kind)`. Awarded from
+/// `with_stats`, so they follow `PlayerStats` and survive `reset_stats`.
+#[spacetimedb::table(name = "achievement", accessor = achievement, public)]
+pub struct Achievement {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub identity: Identity,
+    pub kind: AchievementKind,
+    pub unlocked_at: Timestamp,
+}
 
-#[spacetimedb::reducer]
-pub fn spawn_player(ctx: &ReducerContext, name: String, color: u32) {
-    let identity = ctx.sender();
+/// Top players by total mass (main cell + split cells), rank 1 = heaviest.
+#[spacetimedb::table(name = "leaderboard", accessor = leaderboard, public)]
+pub struct LeaderboardEntry {
+    #[primary_key]
+    pub rank: u32,
+    pub identity: Identity,
+    pub name: String,
+    pub mass: f32,
+    pub team_id: Option<u32>,
+}
 
-    // Remove any stale session data
-    ctx.db.player().identity().delete(identity);
-    delete_player_cells(ctx, identity);
+/// Aggregate mass per team, for team-mode scoring.
+#[spacetimedb::table(name = "team_score", accessor = team_score, public)]
+pub struct TeamScore {
+    #[primary_key]
+    pub team_id: u32,
+    pub total_mass: f32,
+    pub member_count: u32,
+}
 
-    let mut rng = ctx.rng();
-    let x = rng.gen_range(100.0_f32..(WORLD_WIDTH - 100.0));
-    let y = rng.gen_range(100.0_f32..(WORLD_HEIGHT - 100.0));
-
-    // color == 0 means the client wants a random color assigned
-    let final_color = if color == 0 {
-        let colors: &[u32] = &[
-            0x4a90d9, 0xe74c3c, 0x2ecc71, 0xf39c12,
-            0x9b59b6, 0x1abc9c, 0xe91e63, 0x00bcd4,
-        ];
-        colors[rng.gen_range(0..colors.len())]
-    } else {
-        color
-    };
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEventKind {
+    Spawn,
+    Death,
+    Split,
+    Eject,
+    RoundEnd,
+}
 
-    ctx.db.player().insert(Player {
-        identity,
-        name,
-        x,
-        y,
-        radius: mass_to_radius(INITIAL_MASS),
-        mass: INITIAL_MASS,
-        color: final_color,
-    });
+/// Append-only log of notable game events for external consumers (kill feeds,
+/// analytics) to subscribe to. `payload` is a small JSON object whose fields
+/// depend on `kind`. Capped at `MAX_GAME_EVENTS` by `trim_game_events`.
+#[spacetimedb::table(name = "game_event", accessor = game_event, public)]
+pub struct GameEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub kind: GameEventKind,
+    /// The player the event is about, if any.
+    pub identity: Option<Identity>,
+    pub payload: String,
+    pub timestamp: Timestamp,
 }
 
-#[spacetimedb::reducer]
-pub fn despawn_player(ctx: &ReducerContext) {
-    let identity = ctx.sender();
-    ctx.db.player().identity().delete(identity);
-    delete_player_cells(ctx, identity);
+/// Kill feed: one row per player absorbed by another.
+#[spacetimedb::table(name = "kill_event", accessor = kill_event, public)]
+pub struct KillEvent {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub killer_identity: Identity,
+    pub killer_name: String,
+    pub victim_name: String,
+    pub timestamp: Timestamp,
 }
 
-// ---------------------------------------------------------------------------
-// Movement
-// ---------------------------------------------------------------------------
+/// Battle-royale safe zone (singleton, id 0). Absent when the mode is off.
+#[spacetimedb::table(name = "safe_zone", accessor = safe_zone, public)]
+pub struct SafeZone {
+    #[primary_key]
+    pub id: u32,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32,
+    /// Units per second the radius shrinks by.
+    pub shrink_rate: f32,
+}
 
-#[spacetimedb::reducer]
-pub fn update_position(ctx: &ReducerContext, x: f32, y: f32) {
-    let identity = ctx.sender();
-    if let Some(player) = ctx.db.player().identity().find(identity) {
-        let clamped_x = x.clamp(player.radius, WORLD_WIDTH - player.radius);
-        let clamped_y = y.clamp(player.radius, WORLD_HEIGHT - player.radius);
-        ctx.db.player().identity().update(Player {
-            x: clamped_x,
-            y: clamped_y,
-            ..player
-        });
+impl SafeZone {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let (dx, dy) = (x - self.center_x, y - self.center_y);
+        dx * dx + dy * dy <= self.radius * self.radius
     }
 }
 
-/// Update the position of a split-cell half. Caller must own the cell.
-#[spacetimedb::reducer]
-pub fn update_cell_position(ctx: &ReducerContext, cell_id: u64, x: f32, y: f32) {
-    let identity = ctx.sender();
-    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
-    if cell.player_identity != identity { return; }
-    let clamped_x = x.clamp(cell.radius, WORLD_WIDTH - cell.radius);
-    let clamped_y = y.clamp(cell.radius, WORLD_HEIGHT - cell.radius);
-    ctx.db.player_cell().cell_id().update(PlayerCell {
-        x: clamped_x,
-        y: clamped_y,
-        ..cell
-    });
+/// Last time each identity performed a rate-limited action (micros since epoch).
+#[spacetimedb::table(name = "rate_limit", accessor = rate_limit)]
+pub struct RateLimit {
+    #[primary_key]
+    pub identity: Identity,
+    pub last_eject_micros: i64,
+    pub last_split_micros: i64,
 }
 
-// ---------------------------------------------------------------------------
-// Eating
-// ---------------------------------------------------------------------------
+impl RateLimit {
+    /// Record `action` at `now` unless it was last taken under `cooldown_micros` ago.
+    fn try_take(&mut self, action: RateLimitedAction, now: i64, cooldown_micros: i64) -> bool {
+        let last = match action {
+            RateLimitedAction::Eject => &mut self.last_eject_micros,
+            RateLimitedAction::Split => &mut self.last_split_micros,
+        };
+        if now.saturating_sub(*last) < cooldown_micros {
+            return false;
+        }
+        *last = now;
+        true
+    }
+}
 
-#[spacetimedb::reducer]
-pub fn eat_food(ctx: &ReducerContext, food_id: u64) {
-    let identity = ctx.sender();
-    if let Some(player) = ctx.db.player().identity().find(identity) {
-        if let Some(food) = ctx.db.food_pellet().id().find(food_id) {
-            let dx = food.x - player.x;
-            let dy = food.y - player.y;
-            let dist_sq = dx * dx + dy * dy;
-            let eat_dist = player.radius + food.radius;
-            if dist_sq > (eat_dist * 2.0) * (eat_dist * 2.0) {
-                return;
-            }
-            ctx.db.food_pellet().id().delete(food_id);
+/// Reducer calls subject to a per-identity cooldown.
+#[derive(Clone, Copy)]
+enum RateLimitedAction {
+    Eject,
+    Split,
+}
 
-            let new_mass = player.mass + 1.0;
-            ctx.db.player().identity().update(Player {
-                mass: new_mass,
-                radius: mass_to_radius(new_mass),
-                ..player
-            });
+/// Identities allowed to call admin reducers.
+#[spacetimedb::table(name = "admin", accessor = admin)]
+pub struct Admin {
+    #[primary_key]
+    pub identity: Identity,
+}
 
-            let mut rng = ctx.rng();
-            let new_x = rng.gen_range(20.0_f32..(WORLD_WIDTH - 20.0));
-            let new_y = rng.gen_range(20.0_f32..(WORLD_HEIGHT - 20.0));
-            ctx.db.food_pellet().insert(FoodPellet { id: 0, x: new_x, y: new_y, radius: FOOD_RADIUS });
-        }
-    }
+/// Identities barred from connecting or spawning.
+#[spacetimedb::table(name = "ban_list", accessor = ban_list)]
+pub struct BanList {
+    #[primary_key]
+    pub identity: Identity,
+    pub banned_at: Timestamp,
 }
 
-/// Eat another player: caller must be 10%+ larger and overlapping.
-/// The target's entire mass (including any split cells) is absorbed.
-#[spacetimedb::reducer]
-pub fn eat_player(ctx: &ReducerContext, target_identity: Identity) {
-    let eater_id = ctx.sender();
-    if eater_id == target_identity { return; }
+#[spacetimedb::table(name = "chat_message", accessor = chat_message, public)]
+pub struct ChatMessage {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub sender_identity: Identity,
+    pub sender_name: String,
+    pub text: String,
+    pub timestamp: Timestamp,
+}
 
-    let Some(eater) = ctx.db.player().identity().find(eater_id) else { return; };
-    let Some(target) = ctx.db.player().identity().find(target_identity) else { return; };
+/// Green spiky viruses that burst larger players into many cells on contact.
+#[spacetimedb::table(name = "virus", accessor = virus, public)]
+pub struct Virus {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub mass: f32,
+    /// Ejected pellets absorbed since this virus last shot.
+    pub feed_count: u32,
+}
 
-    if eater.mass < target.mass * 1.1 { return; }
+/// Something tracked by the spatial index.
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridEntity {
+    Player(Identity),
+    Food(u64),
+    Cell(u64),
+    EjectedMass(u64),
+    Virus(u64),
+}
 
-    let dx = eater.x - target.x;
-    let dy = eater.y - target.y;
-    let dist_sq = dx * dx + dy * dy;
-    if dist_sq > (eater.radius * 2.0) * (eater.radius * 2.0) { return; }
+/// Spatial index: one row per tracked entity, keyed by the `GRID_CELL_SIZE`
+/// bucket its center sits in. Kept in step with entity positions so proximity
+/// queries (`entities_near`) only touch neighbouring buckets.
+#[spacetimedb::table(name = "grid_cell", accessor = grid_cell)]
+pub struct GridCell {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub bucket: u64,
+    pub entity: GridEntity,
+}
 
-    // Absorb target's split-cell mass too
-    let split_mass: f32 = ctx.db.player_cell().iter()
-        .filter(|c| c.player_identity == target_identity)
-        .map(|c| c.mass)
-        .sum();
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUpKind {
+    SpeedBoost,
+    Shield,
+}
 
-    let new_mass = eater.mass + target.mass + split_mass;
-    ctx.db.player().identity().update(Player {
-        mass: new_mass,
-        radius: mass_to_radius(new_mass),
-        ..eater
-    });
+/// Pickups that grant a timed effect to the player who eats them.
+#[spacetimedb::table(name = "power_up", accessor = power_up, public)]
+pub struct PowerUp {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub kind: PowerUpKind,
+}
 
-    ctx.db.player().identity().delete(target_identity);
-    delete_player_cells(ctx, target_identity);
+/// Tops power-ups back up to `POWERUP_COUNT` every 10 seconds (repeating schedule).
+#[spacetimedb::table(name = "powerup_spawn_schedule", accessor = powerup_spawn_schedule, scheduled(respawn_powerups))]
+pub struct PowerUpSpawnSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
 }
 
-/// Eat a food pellet using a split cell.
-/// Cell must belong to the caller; proximity is checked against the cell position.
-/// Mass is credited to the PlayerCell so it grows correctly before merging.
-#[spacetimedb::reducer]
-pub fn eat_food_cell(ctx: &ReducerContext, cell_id: u64, food_id: u64) {
-    let identity = ctx.sender();
-    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
-    if cell.player_identity != identity { return; }
-    let Some(food) = ctx.db.food_pellet().id().find(food_id) else { return; };
+/// Axis-aligned wall spanning `x..x + width`, `y..y + height`. No cell may
+/// overlap one: moves into it are blocked or slide along it, and cells that
+/// grow into it are pushed out.
+#[spacetimedb::table(name = "obstacle", accessor = obstacle, public)]
+pub struct Obstacle {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
 
-    let dx = food.x - cell.x;
-    let dy = food.y - cell.y;
-    let dist_sq = dx * dx + dy * dy;
-    let eat_dist = cell.radius + food.radius;
-    if dist_sq > (eat_dist * 2.0) * (eat_dist * 2.0) {
-        return;
+impl Obstacle {
+    /// Point of the wall closest to `(x, y)`; `(x, y)` itself if inside.
+    fn closest_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (x.clamp(self.x, self.x + self.width), y.clamp(self.y, self.y + self.height))
     }
 
-    ctx.db.food_pellet().id().delete(food_id);
+    fn overlaps(&self, x: f32, y: f32, radius: f32) -> bool {
+        let (cx, cy) = self.closest_point(x, y);
+        let (dx, dy) = (x - cx, y - cy);
+        dx * dx + dy * dy < radius * radius
+    }
 
-    let new_mass = cell.mass + 1.0;
-    ctx.db.player_cell().cell_id().update(PlayerCell {
-        mass: new_mass,
-        radius: mass_to_radius(new_mass),
-        ..cell
-    });
+    /// Whether a circle of `radius` sweeping from `from` to `to` touches the
+    /// wall anywhere along the way, so fast movers can't tunnel through it.
+    fn blocks_path(&self, from: (f32, f32), to: (f32, f32), radius: f32) -> bool {
+        if self.overlaps(from.0, from.1, radius) || self.overlaps(to.0, to.1, radius) || self.crosses(from, to) {
+            return true;
+        }
+        // Otherwise the path comes closest to the wall at one of its corners
+        let (right, bottom) = (self.x + self.width, self.y + self.height);
+        [(self.x, self.y), (right, self.y), (self.x, bottom), (right, bottom)]
+            .into_iter()
+            .any(|corner| segment_dist_sq(corner, from, to) < radius * radius)
+    }
 
-    let mut rng = ctx.rng();
-    let new_x = rng.gen_range(20.0_f32..(WORLD_WIDTH - 20.0));
-    let new_y = rng.gen_range(20.0_f32..(WORLD_HEIGHT - 20.0));
-    ctx.db.food_pellet().insert(FoodPellet { id: 0, x: new_x, y: new_y, radius: FOOD_RADIUS });
-}
+    /// Whether the segment `from`-`to` passes through the rectangle itself.
+    fn crosses(&self, from: (f32, f32), to: (f32, f32)) -> bool {
+        let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+        let axes = [
+            (from.0, to.0 - from.0, self.x, self.x + self.width),
+            (from.1, to.1 - from.1, self.y, self.y + self.height),
+        ];
+        for (start, delta, lo, hi) in axes {
+            if delta == 0.0 {
+                if start < lo || start > hi { return false; }
+                continue;
+            }
+            let (a, b) = ((lo - start) / delta, (hi - start) / delta);
+            t_min = t_min.max(a.min(b));
+            t_max = t_max.min(a.max(b));
+            if t_min > t_max { return false; }
+        }
+        true
+    }
+
+    /// Nearest center for a circle of `radius` at `(x, y)` that clears the wall.
+    fn push_out(&self, x: f32, y: f32, radius: f32) -> (f32, f32) {
+        if !self.overlaps(x, y, radius) {
+            return (x, y);
+        }
+        let (cx, cy) = self.closest_point(x, y);
+        let (dx, dy) = (x - cx, y - cy);
+        let dist = (dx * dx + dy * dy).sqrt();
+        if dist > 0.0 {
+            return (cx + dx / dist * radius, cy + dy / dist * radius);
+        }
+        // Center is inside: leave through the nearest side
+        let exits = [
+            (x - self.x, (self.x - radius, y)),
+            (self.x + self.width - x, (self.x + self.width + radius, y)),
+            (y - self.y, (x, self.y - radius)),
+            (self.y + self.height - y, (x, self.y + self.height + radius)),
+        ];
+        exits.iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or((x, y), |&(_, exit)| exit)
+    }
+}
+
+/// Two-way portal: a player overlapping either end is moved to the other.
+#[spacetimedb::table(name = "portal", accessor = portal, public)]
+pub struct Portal {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub entry_x: f32,
+    pub entry_y: f32,
+    pub exit_x: f32,
+    pub exit_y: f32,
+    pub radius: f32,
+}
+
+/// Checks players against portals ten times a second (repeating schedule).
+#[spacetimedb::table(name = "portal_schedule", accessor = portal_schedule, scheduled(use_portals))]
+pub struct PortalSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Coarse per-player minimap marker, so clients needn't subscribe to every player.
+/// Rows are only rewritten when a player's grid square or mass bucket changes.
+#[spacetimedb::table(name = "minimap_blip", accessor = minimap_blip, public)]
+pub struct MinimapBlip {
+    #[primary_key]
+    pub identity: Identity,
+    /// Position in `MINIMAP_CELL_SIZE` squares.
+    pub grid_x: u16,
+    pub grid_y: u16,
+    /// `floor(log2(total_mass / initial_mass))`, floored at 0.
+    pub mass_bucket: u8,
+}
+
+/// Refreshes the minimap four times a second (repeating schedule).
+#[spacetimedb::table(name = "minimap_schedule", accessor = minimap_schedule, scheduled(update_minimap))]
+pub struct MinimapSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// One row per match. `winner_identity` is filled in when the round ends.
+#[spacetimedb::table(name = "game_round", accessor = game_round, public)]
+pub struct GameRound {
+    #[primary_key]
+    #[auto_inc]
+    pub round_id: u64,
+    pub started_at: Timestamp,
+    pub ends_at: Timestamp,
+    pub winner_identity: Option<Identity>,
+    pub winner_name: Option<String>,
+}
+
+/// One-time schedule to end a round at its `ends_at`.
+#[spacetimedb::table(name = "round_end_schedule", accessor = round_end_schedule, scheduled(end_round))]
+pub struct RoundEndSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+    pub round_id: u64,
+}
+
+/// Players eaten in hardcore mode this round. They cannot spawn until
+/// `begin_round` clears the table.
+#[spacetimedb::table(name = "graveyard", accessor = graveyard, public)]
+pub struct Graveyard {
+    #[primary_key]
+    pub identity: Identity,
+    pub name: String,
+    pub died_at: Timestamp,
+}
+
+/// Identities watching without a `Player` row. Never eat or get eaten.
+#[spacetimedb::table(name = "spectator", accessor = spectator, public)]
+pub struct Spectator {
+    #[primary_key]
+    pub identity: Identity,
+    /// Player the camera follows, if any.
+    pub following: Option<Identity>,
+}
+
+/// Resizes the world to fit the player count every 10 seconds (repeating schedule).
+#[spacetimedb::table(name = "world_resize_schedule", accessor = world_resize_schedule, scheduled(resize_world))]
+pub struct WorldResizeSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// World-wide mass totals written by `audit_mass`. A sudden `delta` with no
+/// matching food spawns points at a duplication exploit or logic bug.
+#[spacetimedb::table(name = "mass_audit", accessor = mass_audit, public)]
+pub struct MassAudit {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub audited_at: Timestamp,
+    pub player_mass: f32,
+    pub cell_mass: f32,
+    pub food_mass: f32,
+    pub ejected_mass: f32,
+    pub total: f32,
+    /// Change in `total` since the previous audit (0 for the first).
+    pub delta: f32,
+}
+
+/// How many generators `game_rng` has handed out since the seed was last set (id 0).
+#[spacetimedb::table(name = "rng_state", accessor = rng_state)]
+pub struct RngState {
+    #[primary_key]
+    pub id: u32,
+    pub draws: u64,
+}
+
+/// One-row health check (id 0) for ops dashboards, refreshed every
+/// `METRICS_TICK_MICROS` by `refresh_metrics` and on demand by `export_state`.
+#[spacetimedb::table(name = "game_snapshot", accessor = game_snapshot, public)]
+pub struct GameSnapshot {
+    #[primary_key]
+    pub id: u32,
+    pub taken_at: Timestamp,
+    pub player_count: u32,
+    pub cell_count: u32,
+    /// Main cells plus split cells, across all players.
+    pub total_mass: f32,
+    pub food_count: u32,
+    pub ejected_count: u32,
+    pub virus_count: u32,
+}
+
+/// What each identity last spawned with, reused on later spawns and by `respawn`.
+#[spacetimedb::table(name = "last_loadout", accessor = last_loadout)]
+pub struct LastLoadout {
+    #[primary_key]
+    pub identity: Identity,
+    /// Sanitized name as requested, before de-duplication.
+    pub name: String,
+    pub color: u32,
+}
+
+/// Colors a player may pick or be assigned. Seeded from `DEFAULT_PLAYER_COLORS`;
+/// admins edit it with `add_palette_color` / `remove_palette_color`.
+#[spacetimedb::table(name = "color_palette", accessor = color_palette, public)]
+pub struct ColorPalette {
+    #[primary_key]
+    pub color: u32,
+}
+
+/// Hotspot centers for clustered food.
+#[spacetimedb::table(name = "food_cluster", accessor = food_cluster, public)]
+pub struct FoodCluster {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(SpacetimeType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewEntityKind {
+    Player,
+    Cell,
+    Food,
+    EjectedMass,
+}
+
+/// Per-viewer culled snapshot written by `refresh_view`: everything within the
+/// viewer's `view_radius`. A client subscribes to
+/// `SELECT * FROM view_entity WHERE viewer = '<own identity>'` instead of the
+/// full `player` / `player_cell` / `food_pellet` / `ejected_mass` tables, and
+/// calls `refresh_view` whenever it wants a fresh frame.
+#[spacetimedb::table(name = "view_entity", accessor = view_entity, public)]
+pub struct ViewEntity {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub viewer: Identity,
+    pub kind: ViewEntityKind,
+    /// Row id in the source table; 0 for a `Player` (use `owner`).
+    pub entity_id: u64,
+    /// Owning player for `Player` and `Cell` entries.
+    pub owner: Option<Identity>,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+}
+
+/// Per-viewer danger list written by `refresh_threats`: one row per nearby
+/// cell, main or split, that could eat one of the viewer's cells. Clients
+/// subscribe to their own rows to flash a warning.
+#[spacetimedb::table(name = "threat", accessor = threat, public)]
+pub struct Threat {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    #[index(btree)]
+    pub viewer: Identity,
+    pub threat_identity: Identity,
+    /// The threatening split cell, or 0 for the threat's main cell.
+    pub threat_cell_id: u64,
+    /// Mass of the threatening cell.
+    pub mass: f32,
+    /// Center-to-center distance to the nearest viewer cell it could eat.
+    pub distance: f32,
+}
+
+/// Tops food back up to `GameConfig.max_food` every second (repeating schedule).
+#[spacetimedb::table(name = "food_maintain_schedule", accessor = food_maintain_schedule, scheduled(maintain_food))]
+pub struct FoodMaintainSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Shrinks the safe zone every second (repeating schedule).
+#[spacetimedb::table(name = "zone_shrink_schedule", accessor = zone_shrink_schedule, scheduled(shrink_zone))]
+pub struct ZoneShrinkSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Damages cells outside the safe zone every second (repeating schedule).
+#[spacetimedb::table(name = "zone_damage_schedule", accessor = zone_damage_schedule, scheduled(zone_damage))]
+pub struct ZoneDamageSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Marks a synthetic player whose `Player` row is driven by `tick_bots`.
+#[spacetimedb::table(name = "bot", accessor = bot, public)]
+pub struct Bot {
+    #[primary_key]
+    pub identity: Identity,
+    pub index: u32,
+}
+
+/// Drifts food toward small players ten times a second (repeating schedule).
+#[spacetimedb::table(name = "food_magnet_schedule", accessor = food_magnet_schedule, scheduled(attract_food))]
+pub struct FoodMagnetSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Moves every bot five times a second (repeating schedule).
+#[spacetimedb::table(name = "bot_tick_schedule", accessor = bot_tick_schedule, scheduled(tick_bots))]
+pub struct BotTickSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Integrates in-flight ejected mass 20 times a second (repeating schedule).
+#[spacetimedb::table(name = "ejected_motion_schedule", accessor = ejected_motion_schedule, scheduled(update_ejected_motion))]
+pub struct EjectedMotionSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Resolves eating server-side ten times a second (repeating schedule).
+#[spacetimedb::table(name = "collision_schedule", accessor = collision_schedule, scheduled(resolve_collisions))]
+pub struct CollisionSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Integrates split-cell launch velocity and eject impulse 20 times a second (repeating schedule).
+#[spacetimedb::table(name = "split_motion_schedule", accessor = split_motion_schedule, scheduled(update_split_motion))]
+pub struct SplitMotionSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Drives mass decay every `GameConfig.decay_interval_micros` (repeating schedule).
+#[spacetimedb::table(name = "mass_decay_schedule", accessor = mass_decay_schedule, scheduled(decay_mass))]
+pub struct MassDecaySchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Sweeps expired ejected mass every 5 seconds (repeating schedule).
+#[spacetimedb::table(name = "ejected_mass_expiry_schedule", accessor = ejected_mass_expiry_schedule, scheduled(expire_ejected_mass))]
+pub struct EjectedMassExpirySchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Trims old kill feed entries every 5 seconds (repeating schedule).
+#[spacetimedb::table(name = "kill_event_cleanup_schedule", accessor = kill_event_cleanup_schedule, scheduled(trim_kill_events))]
+pub struct KillEventCleanupSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Audits world mass every 10 seconds (repeating schedule).
+#[spacetimedb::table(name = "mass_audit_schedule", accessor = mass_audit_schedule, scheduled(audit_mass))]
+pub struct MassAuditSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Refreshes the `game_snapshot` metrics row every 5 seconds (repeating schedule).
+#[spacetimedb::table(name = "metrics_schedule", accessor = metrics_schedule, scheduled(refresh_metrics))]
+pub struct MetricsSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Despawns idle players every 10 seconds (repeating schedule).
+#[spacetimedb::table(name = "idle_reap_schedule", accessor = idle_reap_schedule, scheduled(reap_idle))]
+pub struct IdleReapSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Caps the game event log every 5 seconds (repeating schedule).
+#[spacetimedb::table(name = "game_event_cleanup_schedule", accessor = game_event_cleanup_schedule, scheduled(trim_game_events))]
+pub struct GameEventCleanupSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Trims old chat messages every 10 seconds (repeating schedule).
+#[spacetimedb::table(name = "chat_cleanup_schedule", accessor = chat_cleanup_schedule, scheduled(trim_chat_messages))]
+pub struct ChatCleanupSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// Tops the virus population back up to `VIRUS_COUNT` every 5 seconds (repeating schedule).
+#[spacetimedb::table(name = "virus_spawn_schedule", accessor = virus_spawn_schedule, scheduled(respawn_viruses))]
+pub struct VirusSpawnSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+}
+
+/// One-time schedule to delete a disconnected player once their grace period ends.
+#[spacetimedb::table(name = "disconnect_schedule", accessor = disconnect_schedule, scheduled(expire_disconnected))]
+pub struct DisconnectSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+    pub player_identity: Identity,
+}
+
+/// One-time schedule to clear a player's `invulnerable` flag when spawn protection lapses.
+#[spacetimedb::table(name = "spawn_protection_schedule", accessor = spawn_protection_schedule, scheduled(expire_spawn_protection))]
+pub struct SpawnProtectionSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+    pub player_identity: Identity,
+}
+
+/// One-time schedule to merge a player's split cells back once their timers lapse.
+/// At most one row per player, due at their earliest pending `merge_at`.
+#[spacetimedb::table(name = "split_merge_schedule", accessor = split_merge_schedule, scheduled(merge_split))]
+pub struct SplitMergeSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: spacetimedb::ScheduleAt,
+    #[index(btree)]
+    pub player_identity: Identity,
+}
+
+// ---------------------------------------------------------------------------
+// Lifecycle
+// ---------------------------------------------------------------------------
+
+#[spacetimedb::reducer(init)]
+pub fn init(ctx: &ReducerContext) {
+    ctx.db.game_config().insert(default_config());
+
+    for identity in admin_identities(ctx.sender(), ADMIN_IDENTITIES) {
+        ctx.db.admin().insert(Admin { identity });
+    }
+
+    for &color in DEFAULT_PLAYER_COLORS {
+        ctx.db.color_palette().insert(ColorPalette { color });
+    }
+
+    // Start the repeating mass-decay schedule
+    schedule_decay(ctx);
+
+    populate_arena(ctx);
+}
+
+/// Wipe the arena between matches: every player, bot, cell, pellet, virus,
+/// power-up, portal, wall, round and pending schedule goes, then the world is
+/// rebuilt exactly as `init` builds it, with food re-seeded up to `max_food`.
+/// The mass audit history goes too, so the first audit of the new arena is a
+/// fresh baseline rather than a huge bogus `delta`.
+/// Config, admins, bans, the palette, lifetime stats, logs and the mass-decay
+/// schedule are kept. Admin only.
+#[spacetimedb::reducer]
+pub fn reset_arena(ctx: &ReducerContext) {
+    if !is_admin(ctx) { return; }
+
+    // Through `remove_player` so playtime is credited and derived rows cleaned up
+    let players: Vec<Identity> = ctx.db.player().iter().map(|p| p.identity).collect();
+    for identity in players {
+        remove_player(ctx, identity);
+    }
+    clear_table(ctx.db.bot());
+    clear_table(ctx.db.player_cell());
+    clear_table(ctx.db.food_pellet());
+    clear_table(ctx.db.food_cluster());
+    clear_table(ctx.db.ejected_mass());
+    clear_table(ctx.db.virus());
+    clear_table(ctx.db.power_up());
+    clear_table(ctx.db.portal());
+    clear_table(ctx.db.obstacle());
+    clear_table(ctx.db.grid_cell());
+    clear_table(ctx.db.view_entity());
+    clear_table(ctx.db.minimap_blip());
+    clear_table(ctx.db.leaderboard());
+    clear_table(ctx.db.team_score());
+    clear_table(ctx.db.kill_event());
+    clear_table(ctx.db.safe_zone());
+    clear_table(ctx.db.game_round());
+    clear_table(ctx.db.graveyard());
+    clear_table(ctx.db.rate_limit());
+    clear_table(ctx.db.rng_state());
+    clear_table(ctx.db.mass_audit());
+
+    clear_table(ctx.db.food_maintain_schedule());
+    clear_table(ctx.db.zone_shrink_schedule());
+    clear_table(ctx.db.zone_damage_schedule());
+    clear_table(ctx.db.virus_spawn_schedule());
+    clear_table(ctx.db.ejected_mass_expiry_schedule());
+    clear_table(ctx.db.kill_event_cleanup_schedule());
+    clear_table(ctx.db.game_event_cleanup_schedule());
+    clear_table(ctx.db.chat_cleanup_schedule());
+    clear_table(ctx.db.powerup_spawn_schedule());
+    clear_table(ctx.db.world_resize_schedule());
+    clear_table(ctx.db.idle_reap_schedule());
+    clear_table(ctx.db.mass_audit_schedule());
+    clear_table(ctx.db.ejected_motion_schedule());
+    clear_table(ctx.db.split_motion_schedule());
+    clear_table(ctx.db.collision_schedule());
+    clear_table(ctx.db.portal_schedule());
+    clear_table(ctx.db.food_magnet_schedule());
+    clear_table(ctx.db.minimap_schedule());
+    clear_table(ctx.db.metrics_schedule());
+    clear_table(ctx.db.bot_tick_schedule());
+    clear_table(ctx.db.round_end_schedule());
+    clear_table(ctx.db.disconnect_schedule());
+    clear_table(ctx.db.spawn_protection_schedule());
+    clear_table(ctx.db.split_merge_schedule());
+
+    populate_arena(ctx);
+}
+
+/// Delete every row of `table`.
+fn clear_table<T: Table>(table: &T) {
+    let rows: Vec<T::Row> = table.iter().collect();
+    for row in rows {
+        table.delete(row);
+    }
+}
+
+/// The `PORTAL_COUNT` portals and `OBSTACLE_COUNT` walls a fresh arena is
+/// laid out with.
+fn arena_layout(cfg: &GameConfig, rng: &mut impl Rng) -> (Vec<Portal>, Vec<Obstacle>) {
+    let portals = (0..PORTAL_COUNT)
+        .map(|_| {
+            let entry = cfg.random_position(rng, 100.0);
+            portal_between(cfg, entry, cfg.random_position(rng, 100.0))
+        })
+        .collect();
+    let walls = (0..OBSTACLE_COUNT)
+        .map(|_| {
+            let width = rng.gen_range(OBSTACLE_MIN_SIDE..OBSTACLE_MAX_SIDE);
+            let height = rng.gen_range(OBSTACLE_MIN_SIDE..OBSTACLE_MAX_SIDE);
+            let (x, y) = cfg.random_position(rng, OBSTACLE_MAX_SIDE);
+            Obstacle { id: 0, x, y, width, height }
+        })
+        .collect();
+    (portals, walls)
+}
+
+/// Seed the world and start every repeating schedule except mass decay.
+fn populate_arena(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+    let mut rng = ctx.rng();
+    seed_food(ctx);
+
+    for _ in 0..VIRUS_COUNT {
+        spawn_virus(ctx);
+    }
+
+    for _ in 0..POWERUP_COUNT {
+        spawn_powerup(ctx);
+    }
+
+    let (portals, walls) = arena_layout(&cfg, &mut rng);
+    for portal in portals {
+        ctx.db.portal().insert(portal);
+    }
+    for wall in walls {
+        ctx.db.obstacle().insert(wall);
+    }
+
+    // Start the repeating food-maintenance and safe-zone schedules
+    let one_sec = TimeDuration::from_micros(1_000_000);
+    ctx.db.food_maintain_schedule().insert(FoodMaintainSchedule {
+        scheduled_id: 0,
+        scheduled_at: one_sec.into(),
+    });
+    ctx.db.zone_shrink_schedule().insert(ZoneShrinkSchedule {
+        scheduled_id: 0,
+        scheduled_at: one_sec.into(),
+    });
+    ctx.db.zone_damage_schedule().insert(ZoneDamageSchedule {
+        scheduled_id: 0,
+        scheduled_at: one_sec.into(),
+    });
+
+    // Start the repeating virus-respawn and cleanup schedules
+    let five_secs = TimeDuration::from_micros(5_000_000);
+    ctx.db.virus_spawn_schedule().insert(VirusSpawnSchedule {
+        scheduled_id: 0,
+        scheduled_at: five_secs.into(),
+    });
+    ctx.db.ejected_mass_expiry_schedule().insert(EjectedMassExpirySchedule {
+        scheduled_id: 0,
+        scheduled_at: five_secs.into(),
+    });
+    ctx.db.kill_event_cleanup_schedule().insert(KillEventCleanupSchedule {
+        scheduled_id: 0,
+        scheduled_at: five_secs.into(),
+    });
+    ctx.db.game_event_cleanup_schedule().insert(GameEventCleanupSchedule {
+        scheduled_id: 0,
+        scheduled_at: five_secs.into(),
+    });
+    let ten_secs = TimeDuration::from_micros(10_000_000);
+    ctx.db.chat_cleanup_schedule().insert(ChatCleanupSchedule {
+        scheduled_id: 0,
+        scheduled_at: ten_secs.into(),
+    });
+    ctx.db.powerup_spawn_schedule().insert(PowerUpSpawnSchedule {
+        scheduled_id: 0,
+        scheduled_at: ten_secs.into(),
+    });
+    ctx.db.world_resize_schedule().insert(WorldResizeSchedule {
+        scheduled_id: 0,
+        scheduled_at: ten_secs.into(),
+    });
+    ctx.db.idle_reap_schedule().insert(IdleReapSchedule {
+        scheduled_id: 0,
+        scheduled_at: ten_secs.into(),
+    });
+    ctx.db.mass_audit_schedule().insert(MassAuditSchedule {
+        scheduled_id: 0,
+        scheduled_at: ten_secs.into(),
+    });
+    let motion_tick = TimeDuration::from_micros(MOTION_TICK_MICROS);
+    ctx.db.ejected_motion_schedule().insert(EjectedMotionSchedule {
+        scheduled_id: 0,
+        scheduled_at: motion_tick.into(),
+    });
+    ctx.db.split_motion_schedule().insert(SplitMotionSchedule {
+        scheduled_id: 0,
+        scheduled_at: motion_tick.into(),
+    });
+
+    let collision_tick = TimeDuration::from_micros(COLLISION_TICK_MICROS);
+    ctx.db.collision_schedule().insert(CollisionSchedule {
+        scheduled_id: 0,
+        scheduled_at: collision_tick.into(),
+    });
+    ctx.db.portal_schedule().insert(PortalSchedule {
+        scheduled_id: 0,
+        scheduled_at: collision_tick.into(),
+    });
+    let magnet_tick = TimeDuration::from_micros(FOOD_MAGNET_TICK_MICROS);
+    ctx.db.food_magnet_schedule().insert(FoodMagnetSchedule {
+        scheduled_id: 0,
+        scheduled_at: magnet_tick.into(),
+    });
+
+    let minimap_tick = TimeDuration::from_micros(MINIMAP_TICK_MICROS);
+    ctx.db.minimap_schedule().insert(MinimapSchedule {
+        scheduled_id: 0,
+        scheduled_at: minimap_tick.into(),
+    });
+    let metrics_tick = TimeDuration::from_micros(METRICS_TICK_MICROS);
+    ctx.db.metrics_schedule().insert(MetricsSchedule {
+        scheduled_id: 0,
+        scheduled_at: metrics_tick.into(),
+    });
+
+    sync_bots(ctx);
+    let bot_tick = TimeDuration::from_micros(BOT_TICK_MICROS);
+    ctx.db.bot_tick_schedule().insert(BotTickSchedule {
+        scheduled_id: 0,
+        scheduled_at: bot_tick.into(),
+    });
+}
+
+/// Retune the world at runtime. A size change scales every entity with the
+/// world so nothing is left outside it. Admin only.
+#[spacetimedb::reducer]
+pub fn set_config(ctx: &ReducerContext, max_food: u32, world_width: u32, world_height: u32) {
+    if !is_admin(ctx) { return; }
+    if world_width < MIN_WORLD_SIZE || world_height < MIN_WORLD_SIZE { return; }
+
+    let cfg = config(ctx);
+    let sx = world_width as f32 / cfg.width();
+    let sy = world_height as f32 / cfg.height();
+    let resized = cfg.world_width != world_width || cfg.world_height != world_height;
+    ctx.db.game_config().id().update(GameConfig {
+        max_food,
+        world_width,
+        world_height,
+        ..cfg
+    });
+    if resized {
+        rescale_world(ctx, sx, sy);
+    }
+}
+
+/// Change the per-cell mass ceiling. Admin only. Existing cells above it keep
+/// their mass until they next grow.
+#[spacetimedb::reducer]
+pub fn set_max_mass(ctx: &ReducerContext, max_mass: f32) {
+    if !is_admin(ctx) { return; }
+    let cfg = config(ctx);
+    if !max_mass.is_finite() || max_mass < cfg.initial_mass { return; }
+
+    ctx.db.game_config().id().update(GameConfig { max_mass, ..cfg });
+}
+
+/// Switch between a rectangular and circular play area. Admin only.
+#[spacetimedb::reducer]
+pub fn set_world_shape(ctx: &ReducerContext, world_shape: WorldShape) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { world_shape, ..cfg });
+}
+
+/// Tune how much faster large cells decay (0 = flat rate). Admin only.
+#[spacetimedb::reducer]
+pub fn set_decay_exponent(ctx: &ReducerContext, decay_mass_exponent: f32) {
+    if !is_admin(ctx) { return; }
+    if !decay_mass_exponent.is_finite() || decay_mass_exponent < 0.0 { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { decay_mass_exponent, ..cfg });
+}
+
+/// Choose how new food is placed; existing pellets stay put. Admin only.
+#[spacetimedb::reducer]
+pub fn set_food_distribution(ctx: &ReducerContext, food_distribution: FoodDistribution) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { food_distribution, ..cfg });
+}
+
+/// Choose how eaten food is replaced. Admin only.
+#[spacetimedb::reducer]
+pub fn set_food_replacement(ctx: &ReducerContext, food_replacement: FoodReplacement) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { food_replacement, ..cfg });
+}
+
+/// Freeze gameplay for maintenance: player actions and scheduled ticks do
+/// nothing until `resume_game`, and one-shot timers keep retrying every
+/// `PAUSED_RETRY_MICROS`. Admin only.
+#[spacetimedb::reducer]
+pub fn pause_game(ctx: &ReducerContext) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { paused: true, ..cfg });
+}
+
+/// Admin only.
+#[spacetimedb::reducer]
+pub fn resume_game(ctx: &ReducerContext) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { paused: false, ..cfg });
+}
+
+/// Turn automatic world resizing on or off. Admin only.
+#[spacetimedb::reducer]
+pub fn set_auto_resize(ctx: &ReducerContext, auto_resize: bool) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { auto_resize, ..cfg });
+}
+
+/// Turn the small-player food magnet on or off. Admin only.
+#[spacetimedb::reducer]
+pub fn set_food_magnet(ctx: &ReducerContext, food_magnet: bool) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { food_magnet, ..cfg });
+}
+
+/// Turn hardcore (permadeath) mode on or off. Anyone already in the graveyard
+/// stays there until the round ends. Admin only.
+#[spacetimedb::reducer]
+pub fn set_hardcore(ctx: &ReducerContext, hardcore: bool) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { hardcore, ..cfg });
+}
+
+/// Set how long a connected player may sit idle before being despawned.
+/// 0 disables idle reaping. Admin only.
+#[spacetimedb::reducer]
+pub fn set_idle_timeout(ctx: &ReducerContext, idle_timeout_micros: i64) {
+    if !is_admin(ctx) { return; }
+    if idle_timeout_micros < 0 { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { idle_timeout_micros, ..cfg });
+}
+
+/// Tune how far ejected mass flies, in ejecting-cell radii. Admin only.
+#[spacetimedb::reducer]
+pub fn set_eject_distance(ctx: &ReducerContext, eject_distance: f32) {
+    if !is_admin(ctx) { return; }
+    if !eject_distance.is_finite() || eject_distance < 0.0 { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { eject_distance, ..cfg });
+}
+
+/// Tune how widely rapid ejects fan out, in radians either side of the aim. Admin only.
+#[spacetimedb::reducer]
+pub fn set_eject_spread(ctx: &ReducerContext, eject_spread: f32) {
+    if !is_admin(ctx) { return; }
+    if !eject_spread.is_finite() || !(0.0..=std::f32::consts::PI).contains(&eject_spread) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { eject_spread, ..cfg });
+}
+
+/// Set how often decay runs and how much it takes; `decay_rate` 1.0 turns it
+/// off. The decay schedule is restarted at the new interval. Admin only.
+#[spacetimedb::reducer]
+pub fn set_decay(ctx: &ReducerContext, decay_interval_micros: i64, decay_rate: f32) {
+    if !is_admin(ctx) { return; }
+    if !valid_decay(decay_interval_micros, decay_rate) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { decay_interval_micros, decay_rate, ..cfg });
+    schedule_decay(ctx);
+}
+
+/// Decay may tick no faster than `MIN_DECAY_INTERVAL_MICROS`, and each tick keeps
+/// a fraction of mass in `(0, 1]`.
+fn valid_decay(decay_interval_micros: i64, decay_rate: f32) -> bool {
+    decay_interval_micros >= MIN_DECAY_INTERVAL_MICROS && decay_rate.is_finite() && decay_rate > 0.0 && decay_rate <= 1.0
+}
+
+/// Set the spawn mass, split threshold and minimum eject amount. Only new spawns
+/// start at the new `initial_mass`; existing players keep their mass. A very
+/// large `min_split_mass` effectively disables splitting. Admin only.
+#[spacetimedb::reducer]
+pub fn set_mass_rules(ctx: &ReducerContext, initial_mass: f32, min_split_mass: f32, eject_mass_amount: f32) {
+    if !is_admin(ctx) { return; }
+    if !all_finite(&[initial_mass, min_split_mass, eject_mass_amount]) { return; }
+    if initial_mass <= 0.0 || min_split_mass < 0.0 || eject_mass_amount <= 0.0 { return; }
+
+    let cfg = config(ctx);
+    if initial_mass > cfg.max_mass { return; }
+    ctx.db.game_config().id().update(GameConfig { initial_mass, min_split_mass, eject_mass_amount, ..cfg });
+}
+
+/// Set how much of a victim's mass a kill awards, and whether the remainder is
+/// scattered as food. Admin only.
+#[spacetimedb::reducer]
+pub fn set_absorb_rule(ctx: &ReducerContext, absorb_ratio: f32, scatter_lost_mass: bool) {
+    if !is_admin(ctx) { return; }
+    if !absorb_ratio.is_finite() || !(0.0..=1.0).contains(&absorb_ratio) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { absorb_ratio, scatter_lost_mass, ..cfg });
+}
+
+/// Choose how much overlap eating needs; `cover_factor` (0..=1) only matters
+/// under `CenterCover`. Admin only.
+#[spacetimedb::reducer]
+pub fn set_eat_overlap(ctx: &ReducerContext, eat_overlap_mode: EatOverlapMode, eat_cover_factor: f32) {
+    if !is_admin(ctx) { return; }
+    if !eat_cover_factor.is_finite() || !(0.0..=1.0).contains(&eat_cover_factor) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { eat_overlap_mode, eat_cover_factor, ..cfg });
+}
+
+/// Set the merge cooldown for new splits: `merge_delay_micros` plus
+/// `merge_delay_per_mass_micros` for every unit of the split cell's mass, so
+/// bigger cells take longer to merge. Existing cells keep their timers. Admin only.
+#[spacetimedb::reducer]
+pub fn set_merge_delay(ctx: &ReducerContext, merge_delay_micros: i64, merge_delay_per_mass_micros: f32) {
+    if !is_admin(ctx) { return; }
+    if merge_delay_micros < 0 { return; }
+    if !merge_delay_per_mass_micros.is_finite() || merge_delay_per_mass_micros < 0.0 { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { merge_delay_micros, merge_delay_per_mass_micros, ..cfg });
+}
+
+/// Set the lightest cell a split may leave behind. Admin only.
+#[spacetimedb::reducer]
+pub fn set_min_cell_mass(ctx: &ReducerContext, min_cell_mass: f32) {
+    if !is_admin(ctx) { return; }
+    if !min_cell_mass.is_finite() || min_cell_mass < 0.0 { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { min_cell_mass, ..cfg });
+}
+
+/// Reshape the mass-to-radius curve and resize ordinary food pellets. Everything
+/// already in the world is resized to match. Admin only.
+#[spacetimedb::reducer]
+pub fn set_size_curve(ctx: &ReducerContext, radius_scale: f32, radius_exponent: f32, food_radius: f32) {
+    if !is_admin(ctx) { return; }
+    if !all_finite(&[radius_scale, radius_exponent, food_radius]) { return; }
+    if radius_scale <= 0.0 || radius_exponent <= 0.0 || food_radius <= 0.0 { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { radius_scale, radius_exponent, food_radius, ..cfg });
+    refresh_radii(ctx);
+}
+
+/// Fix (or with `None`, clear) the layout seed and regenerate all food from
+/// it, so servers given the same seed share the same food layout. Admin only.
+#[spacetimedb::reducer]
+pub fn set_rng_seed(ctx: &ReducerContext, rng_seed: Option<u64>) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { rng_seed, ..cfg });
+    ctx.db.rng_state().id().delete(0);
+
+    let food: Vec<FoodPellet> = ctx.db.food_pellet().iter().collect();
+    for pellet in &food {
+        remove_food(ctx, pellet);
+    }
+    let clusters: Vec<u64> = ctx.db.food_cluster().iter().map(|c| c.id).collect();
+    for id in clusters {
+        ctx.db.food_cluster().id().delete(id);
+    }
+    seed_food(ctx);
+}
+
+/// Change how many bots are kept in the world. Admin only.
+#[spacetimedb::reducer]
+pub fn set_bot_count(ctx: &ReducerContext, bot_count: u32) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { bot_count, ..cfg });
+    sync_bots(ctx);
+}
+
+/// Cap concurrent players. Anyone already in the game stays; only new spawns
+/// are refused while at or over the cap. Admin only.
+#[spacetimedb::reducer]
+pub fn set_max_players(ctx: &ReducerContext, max_players: u32) {
+    if !is_admin(ctx) { return; }
+
+    let cfg = config(ctx);
+    ctx.db.game_config().id().update(GameConfig { max_players, ..cfg });
+}
+
+/// Refuse connections from banned identities.
+#[spacetimedb::reducer(client_connected)]
+pub fn identity_connected(ctx: &ReducerContext) -> Result<(), String> {
+    if is_banned(ctx, ctx.sender()) {
+        return Err("banned".to_string());
+    }
+    Ok(())
+}
+
+/// Keep the player's cells for `RECONNECT_GRACE_MICROS` so a brief network
+/// blip doesn't wipe their progress; `spawn_player` within the window resumes.
+#[spacetimedb::reducer(client_disconnected)]
+pub fn identity_disconnected(ctx: &ReducerContext) {
+    let identity = ctx.sender();
+    ctx.db.spectator().identity().delete(identity);
+    clear_view(ctx, identity);
+    clear_threats(ctx, identity);
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    credit_playtime(ctx, &player);
+    ctx.db.player().identity().update(Player {
+        disconnected_at: Some(ctx.timestamp),
+        ..player
+    });
+
+    let expires_at = ctx.timestamp + TimeDuration::from_micros(RECONNECT_GRACE_MICROS);
+    ctx.db.disconnect_schedule().insert(DisconnectSchedule {
+        scheduled_id: 0,
+        scheduled_at: expires_at.into(),
+        player_identity: identity,
+    });
+}
+
+/// Delete a player whose reconnect grace period has lapsed.
+#[spacetimedb::reducer]
+pub fn expire_disconnected(ctx: &ReducerContext, schedule: DisconnectSchedule) {
+    if let Some(retry_at) = paused_retry(&config(ctx), ctx.timestamp) {
+        ctx.db.disconnect_schedule().insert(DisconnectSchedule {
+            scheduled_id: 0,
+            scheduled_at: retry_at.into(),
+            ..schedule
+        });
+        return;
+    }
+    let identity = schedule.player_identity;
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    if !grace_expired(&player, ctx.timestamp) { return; }
+    remove_player(ctx, identity);
+}
+
+/// Whether `player` is still disconnected `RECONNECT_GRACE_MICROS` after leaving.
+fn grace_expired(player: &Player, now: Timestamp) -> bool {
+    player.disconnected_at
+        .is_some_and(|at| at + TimeDuration::from_micros(RECONNECT_GRACE_MICROS) <= now)
+}
+
+/// Clear the `invulnerable` flag once spawn protection has run out. A stale
+/// schedule from an earlier life is ignored while the new protection lasts.
+#[spacetimedb::reducer]
+pub fn expire_spawn_protection(ctx: &ReducerContext, schedule: SpawnProtectionSchedule) {
+    let identity = schedule.player_identity;
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    if !protection_lapsed(&player, ctx.timestamp) { return; }
+    ctx.db.player().identity().update(Player { invulnerable: false, ..player });
+}
+
+/// Whether `player` still shows the `invulnerable` flag after their spawn
+/// protection ran out at `now`.
+fn protection_lapsed(player: &Player, now: Timestamp) -> bool {
+    player.invulnerable && !is_spawn_protected(player, now)
+}
+
+/// Despawn connected players who haven't acted within the idle timeout,
+/// scattering their mass as food. Bots and disconnected players (who have
+/// their own grace period) are left alone.
+#[spacetimedb::reducer]
+pub fn reap_idle(ctx: &ReducerContext, _schedule: IdleReapSchedule) {
+    let cfg = config(ctx);
+    if cfg.idle_timeout_micros <= 0 || is_paused(ctx) { return; }
+    let cutoff = ctx.timestamp.to_micros_since_unix_epoch() - cfg.idle_timeout_micros;
+    let idle: Vec<Player> = ctx.db.player().iter()
+        .filter(|p| p.disconnected_at.is_none())
+        .filter(|p| p.last_active_at.to_micros_since_unix_epoch() < cutoff)
+        .filter(|p| ctx.db.bot().identity().find(p.identity).is_none())
+        .collect();
+    for player in idle {
+        let mass = total_mass(ctx, player.identity);
+        scatter_food(ctx, player.x, player.y, player.radius, mass);
+        remove_player(ctx, player.identity);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Player management
+// ---------------------------------------------------------------------------
+
+/// Offer `color` (0xRRGGBB) to players. Admin only.
+#[spacetimedb::reducer]
+pub fn add_palette_color(ctx: &ReducerContext, color: u32) {
+    if !is_admin(ctx) { return; }
+    let palette: Vec<u32> = ctx.db.color_palette().iter().map(|c| c.color).collect();
+    if !can_add_color(&palette, color) { return; }
+    ctx.db.color_palette().insert(ColorPalette { color });
+}
+
+/// Withdraw `color` from the palette. Players already wearing it keep it until
+/// they respawn; the last color can't be removed. Admin only.
+#[spacetimedb::reducer]
+pub fn remove_palette_color(ctx: &ReducerContext, color: u32) {
+    if !is_admin(ctx) { return; }
+    let palette: Vec<u32> = ctx.db.color_palette().iter().map(|c| c.color).collect();
+    if !can_remove_color(&palette, color) { return; }
+    ctx.db.color_palette().color().delete(color);
+}
+
+/// Whether `color` is a valid 0xRRGGBB value not yet in `palette`.
+fn can_add_color(palette: &[u32], color: u32) -> bool {
+    color <= 0xffffff && !palette.contains(&color)
+}
+
+/// Whether `color` is in `palette` and isn't its last entry.
+fn can_remove_color(palette: &[u32], color: u32) -> bool {
+    palette.len() > 1 && palette.contains(&color)
+}
+
+/// Spawn (or respawn) the caller. The name is cleaned up and de-duplicated;
+/// overlong names are rejected without touching any existing session.
+/// `color` must be in the `color_palette` table; anything else (e.g. 0) keeps
+/// the caller's previous color if it's still in the palette, or picks one at random.
+/// A player reconnecting within the grace period keeps their mass and position.
+/// New spawns are refused once `max_players` connected players are in the game,
+/// and for anyone in the hardcore `graveyard` until the round ends; the error
+/// says why.
+#[spacetimedb::reducer]
+pub fn spawn_player(ctx: &ReducerContext, name: String, color: u32) -> Result<(), String> {
+    spawn(ctx, ctx.sender(), name, color, None)
+}
+
+/// One-tap respawn with the caller's last name and color. Fails if they have
+/// never spawned, in which case the client must ask for a name, or if the
+/// spawn itself is refused (ban, graveyard, full server).
+#[spacetimedb::reducer]
+pub fn respawn(ctx: &ReducerContext) -> Result<(), String> {
+    let identity = ctx.sender();
+    let Some(loadout) = ctx.db.last_loadout().identity().find(identity) else {
+        return Err("No previous spawn; call spawn_player with a name".to_string());
+    };
+    spawn(ctx, identity, loadout.name, loadout.color, None)
+}
+
+/// Spawn the caller as a member of `team_id`. Teammates cannot eat each other.
+/// Refused for the same reasons as `spawn_player`.
+#[spacetimedb::reducer]
+pub fn spawn_player_team(ctx: &ReducerContext, name: String, team_id: u32) -> Result<(), String> {
+    spawn(ctx, ctx.sender(), name, 0, Some(team_id))
+}
+
+/// Whether `max_players` connected players other than `identity` are already
+/// in the game. Players inside the reconnect grace period don't hold a slot.
+fn is_full(cfg: &GameConfig, players: impl IntoIterator<Item = Player>, identity: Identity) -> bool {
+    let active = players.into_iter()
+        .filter(|p| p.disconnected_at.is_none() && p.identity != identity)
+        .count();
+    active >= cfg.max_players as usize
+}
+
+/// Refuse banned players and anyone in the hardcore graveyard, saying why.
+fn spawn_allowed(banned: bool, buried: bool) -> Result<(), String> {
+    if banned {
+        return Err("Banned".to_string());
+    }
+    if buried {
+        return Err("Eliminated until the round ends".to_string());
+    }
+    Ok(())
+}
+
+/// Shared by every spawn path. `Err` says why the spawn was refused; nothing is
+/// changed in that case.
+fn spawn(ctx: &ReducerContext, identity: Identity, name: String, color: u32, team_id: Option<u32>) -> Result<(), String> {
+    let buried = ctx.db.graveyard().identity().find(identity).is_some();
+    spawn_allowed(is_banned(ctx, identity), buried)?;
+    let Some(name) = sanitize_name(&name) else {
+        return Err(format!("Name longer than {} characters", MAX_NAME_LEN));
+    };
+
+    // Reconnecting inside the grace period resumes the old session as-is
+    if let Some(player) = ctx.db.player().identity().find(identity) {
+        if player.disconnected_at.is_some() {
+            ctx.db.player().identity().update(Player {
+                disconnected_at: None,
+                online_since: ctx.timestamp,
+                last_move_micros: ctx.timestamp.to_micros_since_unix_epoch(),
+                last_active_at: ctx.timestamp,
+                invulnerable: is_spawn_protected(&player, ctx.timestamp),
+                ..player
+            });
+            return Ok(());
+        }
+    }
+
+    let cfg = config(ctx);
+    if is_full(&cfg, ctx.db.player().iter(), identity) {
+        return Err("Server is full".to_string());
+    }
+
+    // Remove any stale session data
+    remove_player(ctx, identity);
+    ctx.db.spectator().identity().delete(identity);
+
+    let mut rng = game_rng(ctx, &cfg);
+    let (x, y) = safe_spawn_position(ctx, &cfg, &mut rng);
+    let radius = cfg.mass_to_radius(cfg.initial_mass);
+    let (x, y) = push_out_of_obstacles(&all_obstacles(ctx), x, y, radius);
+    let (x, y) = cfg.clamp_to_world(x, y, radius);
+
+    let last_color = ctx.db.last_loadout().identity().find(identity).map(|l| l.color);
+    let palette: Vec<u32> = ctx.db.color_palette().iter().map(|p| p.color).collect();
+    let final_color = spawn_color(color, last_color, &palette, &mut rng);
+    let loadout = LastLoadout { identity, name: name.clone(), color: final_color };
+    if last_color.is_some() {
+        ctx.db.last_loadout().identity().update(loadout);
+    } else {
+        ctx.db.last_loadout().insert(loadout);
+    }
+
+    let name = unique_name(ctx, identity, name);
+    let payload = spawn_payload(&name, final_color);
+    let protected_until = ctx.timestamp + TimeDuration::from_micros(SPAWN_PROTECTION_MICROS);
+    ctx.db.player().insert(Player {
+        identity,
+        name,
+        x,
+        y,
+        radius: cfg.mass_to_radius(cfg.initial_mass),
+        mass: cfg.initial_mass,
+        color: final_color,
+        last_move_micros: ctx.timestamp.to_micros_since_unix_epoch(),
+        spawned_at: ctx.timestamp,
+        spawn_protected_until: protected_until,
+        invulnerable: true,
+        team_id,
+        disconnected_at: None,
+        online_since: ctx.timestamp,
+        last_active_at: ctx.timestamp,
+        boost_until: ctx.timestamp,
+        shield_until: ctx.timestamp,
+        teleport_ready_at: ctx.timestamp,
+        score: 0,
+        view_radius: view_radius(&cfg, cfg.initial_mass),
+        vx: 0.0,
+        vy: 0.0,
+    });
+    grid_insert(ctx, GridEntity::Player(identity), x, y);
+    ctx.db.spawn_protection_schedule().insert(SpawnProtectionSchedule {
+        scheduled_id: 0,
+        scheduled_at: protected_until.into(),
+        player_identity: identity,
+    });
+    mass_changed(ctx, identity);
+    emit_event(ctx, GameEventKind::Spawn, Some(identity), payload);
+    Ok(())
+}
+
+/// Honor a palette color; otherwise keep the last one used, or pick at random.
+fn spawn_color(requested: u32, last: Option<u32>, palette: &[u32], rng: &mut impl Rng) -> u32 {
+    if palette.contains(&requested) {
+        requested
+    } else if let Some(last) = last.filter(|c| palette.contains(c)) {
+        last
+    } else if palette.is_empty() {
+        DEFAULT_PLAYER_COLORS[0]
+    } else {
+        palette[rng.gen_range(0..palette.len())]
+    }
+}
+
+#[spacetimedb::reducer]
+pub fn despawn_player(ctx: &ReducerContext) {
+    let identity = ctx.sender();
+    remove_player(ctx, identity);
+}
+
+/// Leave the game (if playing) and watch instead.
+#[spacetimedb::reducer]
+pub fn become_spectator(ctx: &ReducerContext) {
+    let identity = ctx.sender();
+    remove_player(ctx, identity);
+    if ctx.db.spectator().identity().find(identity).is_none() {
+        ctx.db.spectator().insert(Spectator { identity, following: None });
+    }
+}
+
+#[spacetimedb::reducer]
+pub fn leave_spectator(ctx: &ReducerContext) {
+    ctx.db.spectator().identity().delete(ctx.sender());
+}
+
+/// Point the caller's spectator camera at `target` (`None` for free camera).
+#[spacetimedb::reducer]
+pub fn follow_player(ctx: &ReducerContext, target: Option<Identity>) {
+    let Some(spectator) = ctx.db.spectator().identity().find(ctx.sender()) else { return; };
+    if !can_follow(target, |identity| ctx.db.player().identity().find(identity).is_some()) { return; }
+    ctx.db.spectator().identity().update(Spectator { following: target, ..spectator });
+}
+
+/// A spectator may use the free camera or follow anyone with a player row.
+fn can_follow(target: Option<Identity>, is_player: impl Fn(Identity) -> bool) -> bool {
+    target.is_none_or(is_player)
+}
+
+/// Wipe the caller's lifetime stats.
+#[spacetimedb::reducer]
+pub fn reset_stats(ctx: &ReducerContext) {
+    ctx.db.player_stats().identity().delete(ctx.sender());
+}
+
+// ---------------------------------------------------------------------------
+// Moderation
+// ---------------------------------------------------------------------------
+
+/// Write current counts and aggregates to the `game_snapshot` row. Admin only.
+#[spacetimedb::reducer]
+pub fn export_state(ctx: &ReducerContext) {
+    if !is_admin(ctx) { return; }
+    write_snapshot(ctx);
+}
+
+/// Scheduled counterpart of `export_state`, so dashboards can subscribe to the
+/// one `game_snapshot` row instead of counting tables themselves.
+#[spacetimedb::reducer]
+pub fn refresh_metrics(ctx: &ReducerContext, _schedule: MetricsSchedule) {
+    write_snapshot(ctx);
+}
+
+fn write_snapshot(ctx: &ReducerContext) {
+    let player_mass: Vec<f32> = ctx.db.player().iter().map(|p| p.mass).collect();
+    let cell_mass: Vec<f32> = ctx.db.player_cell().iter().map(|c| c.mass).collect();
+    let snapshot = build_snapshot(
+        ctx.timestamp,
+        &player_mass,
+        &cell_mass,
+        ctx.db.food_pellet().count(),
+        ctx.db.ejected_mass().count(),
+        ctx.db.virus().count(),
+    );
+    if ctx.db.game_snapshot().id().find(0).is_some() {
+        ctx.db.game_snapshot().id().update(snapshot);
+    } else {
+        ctx.db.game_snapshot().insert(snapshot);
+    }
+}
+
+/// The single `game_snapshot` row for a world whose main and split cells
+/// weigh `player_mass` and `cell_mass`.
+fn build_snapshot(
+    taken_at: Timestamp,
+    player_mass: &[f32],
+    cell_mass: &[f32],
+    food_count: u64,
+    ejected_count: u64,
+    virus_count: u64,
+) -> GameSnapshot {
+    GameSnapshot {
+        id: 0,
+        taken_at,
+        player_count: player_mass.len() as u32,
+        cell_count: cell_mass.len() as u32,
+        total_mass: player_mass.iter().chain(cell_mass).sum(),
+        food_count: food_count as u32,
+        ejected_count: ejected_count as u32,
+        virus_count: virus_count as u32,
+    }
+}
+
+/// Remove a player from the world. They may spawn again. Admin only.
+#[spacetimedb::reducer]
+pub fn kick_player(ctx: &ReducerContext, target: Identity) {
+    if !is_admin(ctx) { return; }
+    remove_player(ctx, target);
+}
+
+/// Remove a player and bar them from connecting or spawning. Admin only.
+#[spacetimedb::reducer]
+pub fn ban_player(ctx: &ReducerContext, target: Identity) {
+    if !is_admin(ctx) { return; }
+    if target == ctx.sender() { return; }
+    remove_player(ctx, target);
+    if !is_banned(ctx, target) {
+        ctx.db.ban_list().insert(BanList { identity: target, banned_at: ctx.timestamp });
+    }
+}
+
+/// Lift a ban. Admin only.
+#[spacetimedb::reducer]
+pub fn unban_player(ctx: &ReducerContext, target: Identity) {
+    if !is_admin(ctx) { return; }
+    ctx.db.ban_list().identity().delete(target);
+}
+
+// ---------------------------------------------------------------------------
+// Movement
+// ---------------------------------------------------------------------------
+
+#[spacetimedb::reducer]
+pub fn update_position(ctx: &ReducerContext, x: f32, y: f32) {
+    move_player(ctx, ctx.sender(), x, y);
+}
+
+/// Update the position of a split-cell half. Caller must own the cell and still
+/// be in the game; stale cells of an eaten player are never touched.
+/// Subject to the same mass-based speed cap (and boost) as `update_position`.
+#[spacetimedb::reducer]
+pub fn update_cell_position(ctx: &ReducerContext, cell_id: u64, x: f32, y: f32) {
+    move_cell(ctx, ctx.sender(), cell_id, x, y);
+}
+
+/// Apply several moves in one call; `cell_id` 0 is the main cell. Each entry is
+/// validated exactly as `update_position` / `update_cell_position` would, so
+/// unowned cells are skipped. Entries past `MAX_CELLS` are ignored.
+#[spacetimedb::reducer]
+pub fn update_all_positions(ctx: &ReducerContext, moves: Vec<CellMove>) {
+    if is_paused(ctx) { return; }
+    let identity = ctx.sender();
+    for &m in capped_batch(&moves) {
+        if m.cell_id == 0 {
+            move_player(ctx, identity, m.x, m.y);
+        } else {
+            move_cell(ctx, identity, m.cell_id, m.x, m.y);
+        }
+    }
+}
+
+/// The moves of a batch that are applied: one per possible cell, in order.
+fn capped_batch(moves: &[CellMove]) -> &[CellMove] {
+    &moves[..moves.len().min(MAX_CELLS)]
+}
+
+fn move_player(ctx: &ReducerContext, identity: Identity, x: f32, y: f32) {
+    if is_paused(ctx) { return; }
+    if !all_finite(&[x, y]) { return; }
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    let cfg = config(ctx);
+    let mut max_dist = max_move_dist(ctx, &cfg, player.mass, player.last_move_micros);
+    if is_boosted(&player, ctx.timestamp) {
+        max_dist *= BOOST_SPEED_MULTIPLIER;
+    }
+    let (x, y) = capped_move(player.x, player.y, x, y, max_dist);
+    let (x, y) = cfg.clamp_to_world(x, y, player.radius);
+    let (clamped_x, clamped_y) = obstructed_move(&all_obstacles(ctx), (player.x, player.y), (x, y), player.radius);
+    grid_move(ctx, GridEntity::Player(identity), (player.x, player.y), (clamped_x, clamped_y));
+    ctx.db.player().identity().update(Player {
+        x: clamped_x,
+        y: clamped_y,
+        last_move_micros: ctx.timestamp.to_micros_since_unix_epoch(),
+        last_active_at: ctx.timestamp,
+        ..player
+    });
+}
+
+fn move_cell(ctx: &ReducerContext, identity: Identity, cell_id: u64, x: f32, y: f32) {
+    if is_paused(ctx) { return; }
+    if !all_finite(&[x, y]) { return; }
+    mark_active(ctx, identity);
+    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
+    let Some(owner) = live_owner(ctx.db.player().identity().find(identity), &cell) else { return; };
+    let cfg = config(ctx);
+    let mut max_dist = max_move_dist(ctx, &cfg, cell.mass, cell.last_move_micros);
+    if is_boosted(&owner, ctx.timestamp) {
+        max_dist *= BOOST_SPEED_MULTIPLIER;
+    }
+    let (x, y) = capped_move(cell.x, cell.y, x, y, max_dist);
+    let (x, y) = cfg.clamp_to_world(x, y, cell.radius);
+    let (clamped_x, clamped_y) = obstructed_move(&all_obstacles(ctx), (cell.x, cell.y), (x, y), cell.radius);
+    grid_move(ctx, GridEntity::Cell(cell_id), (cell.x, cell.y), (clamped_x, clamped_y));
+    ctx.db.player_cell().cell_id().update(PlayerCell {
+        x: clamped_x,
+        y: clamped_y,
+        last_move_micros: ctx.timestamp.to_micros_since_unix_epoch(),
+        ..cell
+    });
+}
+
+/// The mover's player row if they are still alive and own `cell`. A cell whose
+/// owner was eaten, or that belongs to someone else, must not be moved.
+fn live_owner(mover: Option<Player>, cell: &PlayerCell) -> Option<Player> {
+    mover.filter(|p| p.identity == cell.player_identity)
+}
+
+// ---------------------------------------------------------------------------
+// Eating
+// ---------------------------------------------------------------------------
+
+/// Deprecated: food is eaten by `resolve_collisions`. Kept as a no-op so
+/// existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_food(_ctx: &ReducerContext, _food_id: u64) {}
+
+/// Eat the closest pellet the caller's main cell overlaps, if any, without
+/// waiting for the next `resolve_collisions` tick. The pellet is chosen by the
+/// server from the spatial index; clients never name it.
+#[spacetimedb::reducer]
+pub fn eat_nearby_food(ctx: &ReducerContext) {
+    if is_paused(ctx) { return; }
+    let identity = ctx.sender();
+    mark_active(ctx, identity);
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+
+    let nearby = entities_near(ctx, player.x, player.y, player.radius + config(ctx).max_food_radius());
+    if let Some(food) = closest_food(&player, nearby_food(ctx, &nearby)) {
+        try_eat_food(ctx, identity, food.id);
+    }
+}
+
+/// The pellet nearest `player`'s center among those it touches.
+fn closest_food(player: &Player, food: impl Iterator<Item = FoodPellet>) -> Option<FoodPellet> {
+    food.filter(|f| within_reach(player.x, player.y, f.x, f.y, player.radius + f.radius))
+        .map(|f| {
+            let (dx, dy) = (f.x - player.x, f.y - player.y);
+            (dx * dx + dy * dy, f)
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, f)| f)
+}
+
+/// Eat a food pellet with `identity`'s main cell if it is within reach.
+fn try_eat_food(ctx: &ReducerContext, identity: Identity, food_id: u64) {
+    if let Some(player) = ctx.db.player().identity().find(identity) {
+        if let Some(food) = ctx.db.food_pellet().id().find(food_id) {
+            if !within_reach(player.x, player.y, food.x, food.y, player.radius + food.radius) {
+                return;
+            }
+            remove_food(ctx, &food);
+            replace_eaten_food(ctx);
+            with_stats(ctx, identity, |stats| stats.food_eaten += 1);
+
+            let new_mass = player.mass + food.mass;
+            grow_player(ctx, Player { score: player.score + food_points(&food), ..player }, new_mass);
+            mass_changed(ctx, identity);
+        }
+    }
+}
+
+/// Deprecated: players are eaten by `resolve_collisions`. Kept as a no-op so
+/// existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_player(_ctx: &ReducerContext, _target_identity: Identity) {}
+
+/// Eat another player with the eater's main cell, which must overlap the target's
+/// main cell as `eat_overlap_mode` requires and be heavier than the target's whole mass (see
+/// `EAT_MASS_RATIO`).
+/// The target's entire mass (including any split cells) is absorbed.
+/// Targets that are `is_uneatable` or on the eater's team cannot be eaten.
+fn try_eat_player(ctx: &ReducerContext, eater_id: Identity, target_identity: Identity) {
+    if eater_id == target_identity { return; }
+
+    let Some(eater) = ctx.db.player().identity().find(eater_id) else { return; };
+    let Some(target) = ctx.db.player().identity().find(target_identity) else { return; };
+    let target_total = total_mass(ctx, target_identity);
+    if !can_eat_player(&config(ctx), &eater, &target, target_total, ctx.timestamp) { return; }
+
+    let Some((absorbed, target_score)) = consume_player(ctx, eater_id, target_identity) else { return; };
+
+    // Mass beyond the cap is discarded; eating forfeits the eater's spawn protection
+    let new_mass = eater.mass + absorbed;
+    let score = eater.score + kill_points(target_score);
+    grow_player(ctx, Player { score, ..unprotected(eater, ctx.timestamp) }, new_mass);
+
+    with_stats(ctx, eater_id, |stats| stats.players_eaten += 1);
+    mass_changed(ctx, eater_id);
+}
+
+/// Whether `eater`'s main cell may eat `target`, whose cells weigh `target_total`
+/// in all. Eating the main cell absorbs the target's split cells too, so the
+/// mass rule compares against the whole player.
+fn can_eat_player(cfg: &GameConfig, eater: &Player, target: &Player, target_total: f32, now: Timestamp) -> bool {
+    !is_uneatable(target, now)
+        && !same_team(eater, target)
+        && can_absorb(eater.mass, target_total)
+        && within_reach(eater.x, eater.y, target.x, target.y, cfg.eat_reach(eater.radius, target.radius))
+}
+
+/// Re-read the victim, log the kill and remove them, returning the mass the
+/// eater gains (`absorb_ratio` of the victim's total, split cells included) and
+/// the victim's score. `None` if the victim fails `still_consumable`; eaters
+/// credit only what this returns, so one victim can never be absorbed twice,
+/// even by several eaters in the same collision tick.
+fn consume_player(ctx: &ReducerContext, eater_id: Identity, target_identity: Identity) -> Option<(f32, u64)> {
+    let target = ctx.db.player().identity().find(target_identity)
+        .filter(|t| still_consumable(t, ctx.timestamp))?;
+    let cfg = config(ctx);
+    let total = total_mass(ctx, target_identity);
+    let absorbed = total * cfg.absorb_ratio;
+    if cfg.scatter_lost_mass {
+        scatter_food(ctx, target.x, target.y, target.radius, total - absorbed);
+    }
+    let score = target.score;
+    with_stats(ctx, target_identity, |stats| stats.deaths += 1);
+    let buried = ctx.db.graveyard().identity().find(target_identity).is_some();
+    if let Some(grave) = grave_for(&cfg, &target, buried, ctx.timestamp) {
+        ctx.db.graveyard().insert(grave);
+    }
+    record_kill(ctx, eater_id, &target);
+    remove_player(ctx, target_identity);
+    Some((absorbed, score))
+}
+
+/// The graveyard row for `target` eaten at `now`: only in hardcore mode, and
+/// only if they aren't `buried` already this round.
+fn grave_for(cfg: &GameConfig, target: &Player, buried: bool, now: Timestamp) -> Option<Graveyard> {
+    (cfg.hardcore && !buried).then(|| Graveyard {
+        identity: target.identity,
+        name: target.name.clone(),
+        died_at: now,
+    })
+}
+
+/// Whether a freshly re-read victim row can still be eaten at `now`: it must be
+/// alive (finite, positive mass) and not a new life spawned during this same
+/// transaction, which is what a bot eaten and respawned earlier in the tick
+/// looks like.
+fn still_consumable(target: &Player, now: Timestamp) -> bool {
+    target.mass.is_finite() && target.mass > 0.0 && target.spawned_at < now
+}
+
+/// Deprecated: food is eaten by `resolve_collisions`. Kept as a no-op so
+/// existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_food_cell(_ctx: &ReducerContext, _cell_id: u64, _food_id: u64) {}
+
+/// Eat a food pellet using a split cell.
+/// Cell must belong to `identity`; proximity is checked against the cell position.
+/// Mass is credited to the PlayerCell so it grows correctly before merging.
+fn try_eat_food_cell(ctx: &ReducerContext, identity: Identity, cell_id: u64, food_id: u64) {
+    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
+    if cell.player_identity != identity { return; }
+    let Some(food) = ctx.db.food_pellet().id().find(food_id) else { return; };
+
+    if !within_reach(cell.x, cell.y, food.x, food.y, cell.radius + food.radius) {
+        return;
+    }
+
+    remove_food(ctx, &food);
+    replace_eaten_food(ctx);
+    with_stats(ctx, identity, |stats| stats.food_eaten += 1);
+
+    let new_mass = cell.mass + food.mass;
+    grow_cell(ctx, cell, new_mass);
+    add_score(ctx, identity, food_points(&food));
+    mass_changed(ctx, identity);
+}
+
+/// Deprecated: players are eaten by `resolve_collisions`. Kept as a no-op so
+/// existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_player_cell(_ctx: &ReducerContext, _cell_id: u64, _target_identity: Identity) {}
+
+/// Eat another player using a split cell.
+/// Cell must belong to `eater_id`, overlap the target's main cell as
+/// `eat_overlap_mode` requires, and be heavier
+/// than the target's whole mass (see `EAT_MASS_RATIO`).
+/// The target's entire mass (including any split cells) is absorbed into this cell.
+fn try_eat_player_cell(ctx: &ReducerContext, eater_id: Identity, cell_id: u64, target_identity: Identity) {
+    if eater_id == target_identity { return; }
+
+    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
+    if cell.player_identity != eater_id { return; }
+
+    let Some(target) = ctx.db.player().identity().find(target_identity) else { return; };
+    if is_uneatable(&target, ctx.timestamp) { return; }
+    let Some(eater) = ctx.db.player().identity().find(eater_id) else { return; };
+    if same_team(&eater, &target) { return; }
+
+    // Eating the main cell absorbs the target's split cells too
+    let split_mass = total_mass(ctx, target_identity) - target.mass;
+    if !can_absorb(cell.mass, target.mass + split_mass) { return; }
+
+    let reach = config(ctx).eat_reach(cell.radius, target.radius);
+    if !within_reach(cell.x, cell.y, target.x, target.y, reach) { return; }
+
+    let Some((absorbed, target_score)) = consume_player(ctx, eater_id, target_identity) else { return; };
+
+    let new_cell_mass = cell.mass + absorbed;
+    grow_cell(ctx, cell, new_cell_mass);
+    end_spawn_protection(ctx, eater_id);
+    add_score(ctx, eater_id, kill_points(target_score));
+
+    with_stats(ctx, eater_id, |stats| stats.players_eaten += 1);
+    mass_changed(ctx, eater_id);
+}
+
+/// Authoritative eating pass: every main cell and split cell eats the food,
+/// players, split cells and ejected mass it overlaps, and main cells burst on
+/// viruses, heaviest players first so prey never eats its hunter. Each
+/// player's own split cells past their cooldown then merge on contact.
+#[spacetimedb::reducer]
+pub fn resolve_collisions(ctx: &ReducerContext, _schedule: CollisionSchedule) {
+    if is_paused(ctx) { return; }
+    let cfg = config(ctx);
+    for identity in hunting_order(ctx.db.player().iter()) {
+        let Some(player) = ctx.db.player().identity().find(identity) else { continue; };
+        for entity in entities_near(ctx, player.x, player.y, contact_reach(&cfg, player.radius)) {
+            match entity {
+                GridEntity::Food(food_id) => try_eat_food(ctx, identity, food_id),
+                GridEntity::Player(target) => try_eat_player(ctx, identity, target),
+                GridEntity::Cell(target_cell_id) => try_eat_cell(ctx, identity, 0, target_cell_id),
+                GridEntity::EjectedMass(mass_id) => try_eat_ejected_mass(ctx, identity, mass_id),
+                GridEntity::Virus(virus_id) => try_eat_virus(ctx, identity, virus_id),
+            }
+        }
+
+        let cells: Vec<PlayerCell> = ctx.db.player_cell().player_identity().filter(identity).collect();
+        let cell_ids: Vec<u64> = cells.iter().map(|c| c.cell_id).collect();
+        for cell in cells {
+            for entity in entities_near(ctx, cell.x, cell.y, contact_reach(&cfg, cell.radius)) {
+                match entity {
+                    GridEntity::Food(food_id) => try_eat_food_cell(ctx, identity, cell.cell_id, food_id),
+                    GridEntity::Player(target) => try_eat_player_cell(ctx, identity, cell.cell_id, target),
+                    GridEntity::Cell(target_cell_id) => try_eat_cell(ctx, identity, cell.cell_id, target_cell_id),
+                    GridEntity::EjectedMass(mass_id) => try_eat_ejected_mass_cell(ctx, identity, cell.cell_id, mass_id),
+                    // Only a main cell can burst on a virus
+                    GridEntity::Virus(_) => {}
+                }
+            }
+        }
+
+        // Own cells past their cooldown merge as soon as they touch
+        for (i, &a) in cell_ids.iter().enumerate() {
+            for &b in &cell_ids[i + 1..] {
+                try_merge_cells(ctx, identity, a, b);
+            }
+        }
+    }
+}
+
+/// Players that eat this tick, heaviest first. Disconnected players are
+/// ghosts: they can neither be eaten (`is_uneatable`) nor eat.
+fn hunting_order(players: impl IntoIterator<Item = Player>) -> Vec<Identity> {
+    let mut hunters: Vec<(Identity, f32)> = players.into_iter()
+        .filter(|p| p.disconnected_at.is_none())
+        .map(|p| (p.identity, p.mass))
+        .collect();
+    hunters.sort_by(|a, b| b.1.total_cmp(&a.1));
+    hunters.into_iter().map(|(identity, _)| identity).collect()
+}
+
+/// Merge two of the caller's split cells that overlap and have both passed
+/// their merge cooldown. The merged cell keeps `cell_a`'s id.
+#[spacetimedb::reducer]
+pub fn attempt_merge(ctx: &ReducerContext, cell_a: u64, cell_b: u64) {
+    if is_paused(ctx) { return; }
+    try_merge_cells(ctx, ctx.sender(), cell_a, cell_b);
+}
+
+fn try_merge_cells(ctx: &ReducerContext, identity: Identity, cell_a: u64, cell_b: u64) {
+    if cell_a == cell_b { return; }
+    let Some(a) = ctx.db.player_cell().cell_id().find(cell_a) else { return; };
+    let Some(b) = ctx.db.player_cell().cell_id().find(cell_b) else { return; };
+    if a.player_identity != identity { return; }
+    if !can_merge(&a, &b, ctx.timestamp) { return; }
+
+    let new_mass = a.mass + b.mass;
+    remove_cell(ctx, &b);
+    grow_cell(ctx, a, new_mass);
+    mass_changed(ctx, identity);
+}
+
+/// Two cells merge once they share an owner, both cooldowns are over, and they touch.
+fn can_merge(a: &PlayerCell, b: &PlayerCell, now: Timestamp) -> bool {
+    a.player_identity == b.player_identity
+        && a.merge_at <= now
+        && b.merge_at <= now
+        && within_reach(a.x, a.y, b.x, b.y, a.radius + b.radius)
+}
+
+/// Farthest center-to-center distance at which a cell of `radius` can touch
+/// something it could eat: any edible cell is smaller than it, and food is tiny.
+fn contact_reach(cfg: &GameConfig, radius: f32) -> f32 {
+    (radius * 2.0).max(radius + cfg.max_food_radius())
+}
+
+/// Deprecated: split cells are eaten by `resolve_collisions`. Kept as a no-op so
+/// existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_cell(_ctx: &ReducerContext, _cell_id: u64, _target_cell_id: u64) {}
+
+/// Eat a single split cell. `cell_id` picks the eating cell, with 0
+/// meaning the main cell.
+///
+/// Another player's cell must be covered by, and `EAT_MASS_RATIO` lighter than,
+/// the eating cell alone (its center inside the eater in `EdgeTouch` mode, the
+/// stricter `CenterCover` rule otherwise); its owner survives. The eater's own cells cannot eat each other
+/// until both merge timers have elapsed; after that, overlapping own cells merge
+/// immediately regardless of size.
+fn try_eat_cell(ctx: &ReducerContext, eater_id: Identity, cell_id: u64, target_cell_id: u64) {
+    if cell_id == target_cell_id { return; }
+    let Some(target) = ctx.db.player_cell().cell_id().find(target_cell_id) else { return; };
+    let own_cell = target.player_identity == eater_id;
+
+    let Some(eater) = ctx.db.player().identity().find(eater_id) else { return; };
+    if !own_cell {
+        if let Some(owner) = ctx.db.player().identity().find(target.player_identity) {
+            if is_uneatable(&owner, ctx.timestamp) { return; }
+            if same_team(&eater, &owner) { return; }
+        }
+    }
+
+    let (x, y, radius, mass) = if cell_id == 0 {
+        (eater.x, eater.y, eater.radius, eater.mass)
+    } else {
+        let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
+        if cell.player_identity != eater_id { return; }
+        if own_cell && cell.merge_at > ctx.timestamp { return; }
+        (cell.x, cell.y, cell.radius, cell.mass)
+    };
+
+    if !can_eat_cell(&config(ctx), (x, y, radius, mass), &target, own_cell, ctx.timestamp) { return; }
+
+    // Another player's cell is subject to the absorb rule; own cells merge whole
+    let gained = if own_cell {
+        target.mass
+    } else {
+        let cfg = config(ctx);
+        let gained = target.mass * cfg.absorb_ratio;
+        if cfg.scatter_lost_mass {
+            scatter_food(ctx, target.x, target.y, target.radius, target.mass - gained);
+        }
+        gained
+    };
+    let new_mass = mass + gained;
+    if cell_id == 0 {
+        grow_player(ctx, eater, new_mass);
+    } else if let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) {
+        grow_cell(ctx, cell, new_mass);
+    }
+
+    remove_cell(ctx, &target);
+    mass_changed(ctx, eater_id);
+    mass_changed(ctx, target.player_identity);
+}
+
+/// Whether an eating cell at `(x, y, radius, mass)` may take `target`: an own
+/// cell once its merge timer is up and the two overlap, another player's cell
+/// only under the absorb and overlap rules.
+fn can_eat_cell(
+    cfg: &GameConfig,
+    (x, y, radius, mass): (f32, f32, f32, f32),
+    target: &PlayerCell,
+    own_cell: bool,
+    now: Timestamp,
+) -> bool {
+    if own_cell {
+        target.merge_at <= now && within_reach(x, y, target.x, target.y, radius.max(target.radius))
+    } else {
+        let reach = cfg.eat_reach(radius, target.radius).min(radius);
+        can_absorb(mass, target.mass) && within_reach(x, y, target.x, target.y, reach)
+    }
+}
+
+/// Deprecated: ejected mass is eaten by `resolve_collisions`. Kept as a no-op
+/// so existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_ejected_mass(_ctx: &ReducerContext, _mass_id: u64) {}
+
+/// Eat an ejected mass pellet with `identity`'s main cell. A player's own
+/// pellets are off limits to them for `SELF_EAT_DELAY_MICROS` after ejecting.
+fn try_eat_ejected_mass(ctx: &ReducerContext, identity: Identity, mass_id: u64) {
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    let Some(em) = ctx.db.ejected_mass().id().find(mass_id) else { return; };
+    if !can_eat_eject(identity, (player.x, player.y, player.radius), &em, ctx.timestamp) { return; }
+
+    remove_ejected_mass(ctx, &em);
+    let new_mass = player.mass + em.mass;
+    grow_player(ctx, player, new_mass);
+    mass_changed(ctx, identity);
+}
+
+/// Deprecated: ejected mass is eaten by `resolve_collisions`. Kept as a no-op
+/// so existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_ejected_mass_cell(_ctx: &ReducerContext, _cell_id: u64, _mass_id: u64) {}
+
+/// Eat an ejected mass pellet using a split cell.
+/// Cell must belong to `identity`; proximity is checked against the cell position.
+fn try_eat_ejected_mass_cell(ctx: &ReducerContext, identity: Identity, cell_id: u64, mass_id: u64) {
+    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
+    if cell.player_identity != identity { return; }
+    let Some(em) = ctx.db.ejected_mass().id().find(mass_id) else { return; };
+    if !can_eat_eject(identity, (cell.x, cell.y, cell.radius), &em, ctx.timestamp) { return; }
+
+    remove_ejected_mass(ctx, &em);
+    let new_mass = cell.mass + em.mass;
+    grow_cell(ctx, cell, new_mass);
+    mass_changed(ctx, identity);
+}
+
+// ---------------------------------------------------------------------------
+// World size (scheduled)
+// ---------------------------------------------------------------------------
+
+/// Grow or shrink the world so each live player has `WORLD_AREA_PER_PLAYER`,
+/// keeping its aspect ratio and the side within the auto-resize bounds. Every
+/// positioned entity is scaled with it, and the food target tracks the area.
+#[spacetimedb::reducer]
+pub fn resize_world(ctx: &ReducerContext, _schedule: WorldResizeSchedule) {
+    if is_paused(ctx) { return; }
+    let cfg = config(ctx);
+    if !cfg.auto_resize { return; }
+
+    let Some(scale) = resize_scale(&cfg, ctx.db.player().count()) else { return; };
+
+    let world_width = (cfg.width() * scale).round() as u32;
+    let world_height = (cfg.height() * scale).round() as u32;
+    let max_food = (cfg.max_food as f32 * scale * scale).round() as u32;
+    let sx = world_width as f32 / cfg.width();
+    let sy = world_height as f32 / cfg.height();
+    ctx.db.game_config().id().update(GameConfig { max_food, world_width, world_height, ..cfg });
+    rescale_world(ctx, sx, sy);
+}
+
+/// Factor to scale the world's sides by so `players` each get
+/// `WORLD_AREA_PER_PLAYER`, or `None` if that is within `WORLD_RESIZE_THRESHOLD`.
+fn resize_scale(cfg: &GameConfig, players: u64) -> Option<f32> {
+    let area = cfg.width() * cfg.height();
+    let target_side = (players.max(1) as f32 * WORLD_AREA_PER_PLAYER).sqrt()
+        .clamp(MIN_AUTO_WORLD_SIZE, MAX_AUTO_WORLD_SIZE);
+    let scale = target_side / area.sqrt();
+    ((scale - 1.0).abs() >= WORLD_RESIZE_THRESHOLD).then_some(scale)
+}
+
+/// Multiply every entity position (and wall size) by `(sx, sy)` and re-clamp
+/// it into the world. Cells scaled into a wall are pushed back out of it.
+fn rescale_world(ctx: &ReducerContext, sx: f32, sy: f32) {
+    let cfg = config(ctx);
+
+    let obstacles: Vec<Obstacle> = ctx.db.obstacle().iter().collect();
+    for obstacle in obstacles {
+        ctx.db.obstacle().id().update(Obstacle {
+            x: obstacle.x * sx,
+            y: obstacle.y * sy,
+            width: obstacle.width * sx,
+            height: obstacle.height * sy,
+            ..obstacle
+        });
+    }
+    let obstacles = all_obstacles(ctx);
+    let players: Vec<Player> = ctx.db.player().iter().collect();
+    for player in players {
+        let (x, y) = push_out_of_obstacles(&obstacles, player.x * sx, player.y * sy, player.radius);
+        let (x, y) = cfg.clamp_to_world(x, y, player.radius);
+        grid_move(ctx, GridEntity::Player(player.identity), (player.x, player.y), (x, y));
+        ctx.db.player().identity().update(Player { x, y, ..player });
+    }
+    let cells: Vec<PlayerCell> = ctx.db.player_cell().iter().collect();
+    for cell in cells {
+        let (x, y) = push_out_of_obstacles(&obstacles, cell.x * sx, cell.y * sy, cell.radius);
+        let (x, y) = cfg.clamp_to_world(x, y, cell.radius);
+        grid_move(ctx, GridEntity::Cell(cell.cell_id), (cell.x, cell.y), (x, y));
+        ctx.db.player_cell().cell_id().update(PlayerCell { x, y, ..cell });
+    }
+    let food: Vec<FoodPellet> = ctx.db.food_pellet().iter().collect();
+    for pellet in food {
+        let (x, y) = cfg.clamp_to_world(pellet.x * sx, pellet.y * sy, pellet.radius);
+        grid_move(ctx, GridEntity::Food(pellet.id), (pellet.x, pellet.y), (x, y));
+        ctx.db.food_pellet().id().update(FoodPellet { x, y, ..pellet });
+    }
+    let ejected: Vec<EjectedMass> = ctx.db.ejected_mass().iter().collect();
+    for em in ejected {
+        let (x, y) = cfg.clamp_to_world(em.x * sx, em.y * sy, em.radius);
+        grid_move(ctx, GridEntity::EjectedMass(em.id), (em.x, em.y), (x, y));
+        ctx.db.ejected_mass().id().update(EjectedMass { x, y, ..em });
+    }
+    let clusters: Vec<FoodCluster> = ctx.db.food_cluster().iter().collect();
+    for cluster in clusters {
+        let (x, y) = cfg.clamp_to_world(cluster.x * sx, cluster.y * sy, 100.0);
+        ctx.db.food_cluster().id().update(FoodCluster { x, y, ..cluster });
+    }
+    let viruses: Vec<Virus> = ctx.db.virus().iter().collect();
+    for virus in viruses {
+        let (x, y) = cfg.clamp_to_world(virus.x * sx, virus.y * sy, virus.radius);
+        grid_move(ctx, GridEntity::Virus(virus.id), (virus.x, virus.y), (x, y));
+        ctx.db.virus().id().update(Virus { x, y, ..virus });
+    }
+    let powerups: Vec<PowerUp> = ctx.db.power_up().iter().collect();
+    for powerup in powerups {
+        let (x, y) = cfg.clamp_to_world(powerup.x * sx, powerup.y * sy, POWERUP_RADIUS);
+        ctx.db.power_up().id().update(PowerUp { x, y, ..powerup });
+    }
+    let portals: Vec<Portal> = ctx.db.portal().iter().collect();
+    for portal in portals {
+        let (entry_x, entry_y) = cfg.clamp_to_world(portal.entry_x * sx, portal.entry_y * sy, portal.radius);
+        let (exit_x, exit_y) = cfg.clamp_to_world(portal.exit_x * sx, portal.exit_y * sy, portal.radius);
+        ctx.db.portal().id().update(Portal { entry_x, entry_y, exit_x, exit_y, ..portal });
+    }
+    if let Some(zone) = ctx.db.safe_zone().id().find(0) {
+        ctx.db.safe_zone().id().update(SafeZone {
+            center_x: zone.center_x * sx,
+            center_y: zone.center_y * sy,
+            radius: zone.radius * sx.max(sy),
+            ..zone
+        });
+    }
+}
+
+/// Recompute every radius from the current size curve, pulling cells back
+/// inside the world if they grew past the edge.
+fn refresh_radii(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+
+    let players: Vec<Player> = ctx.db.player().iter().collect();
+    for player in players {
+        let radius = cfg.mass_to_radius(player.mass);
+        let (x, y) = cfg.clamp_to_world(player.x, player.y, radius);
+        grid_move(ctx, GridEntity::Player(player.identity), (player.x, player.y), (x, y));
+        ctx.db.player().identity().update(Player { x, y, radius, ..player });
+    }
+    let cells: Vec<PlayerCell> = ctx.db.player_cell().iter().collect();
+    for cell in cells {
+        let radius = cfg.mass_to_radius(cell.mass);
+        let (x, y) = cfg.clamp_to_world(cell.x, cell.y, radius);
+        grid_move(ctx, GridEntity::Cell(cell.cell_id), (cell.x, cell.y), (x, y));
+        ctx.db.player_cell().cell_id().update(PlayerCell { x, y, radius, ..cell });
+    }
+    let food: Vec<FoodPellet> = ctx.db.food_pellet().iter().filter(|f| f.mass == FOOD_MASS).collect();
+    for pellet in food {
+        ctx.db.food_pellet().id().update(FoodPellet { radius: cfg.food_radius, ..pellet });
+    }
+    let ejected: Vec<EjectedMass> = ctx.db.ejected_mass().iter().collect();
+    for em in ejected {
+        ctx.db.ejected_mass().id().update(EjectedMass { radius: cfg.mass_to_radius(em.mass), ..em });
+    }
+    let viruses: Vec<Virus> = ctx.db.virus().iter().collect();
+    for virus in viruses {
+        ctx.db.virus().id().update(Virus { radius: cfg.mass_to_radius(virus.mass), ..virus });
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Food supply (scheduled)
+// ---------------------------------------------------------------------------
+
+/// Insert enough pellets to bring the world back up to its configured food
+/// target, subject to `food_replacement`.
+#[spacetimedb::reducer]
+pub fn maintain_food(ctx: &ReducerContext, _schedule: FoodMaintainSchedule) {
+    if is_paused(ctx) { return; }
+    let cfg = config(ctx);
+    spawn_food(ctx, food_refill(&cfg, ctx.db.food_pellet().count()));
+}
+
+/// How many pellets `maintain_food` adds to a world holding `count`.
+fn food_refill(cfg: &GameConfig, count: u64) -> u32 {
+    let target = cfg.max_food as u64;
+    if cfg.food_replacement == FoodReplacement::Burst
+        && count as f32 >= target as f32 * FOOD_BURST_THRESHOLD
+    {
+        return 0;
+    }
+    target.saturating_sub(count) as u32
+}
+
+/// Replace a pellet that was just eaten, if `food_replacement` calls for it.
+fn replace_eaten_food(ctx: &ReducerContext) {
+    if config(ctx).food_replacement == FoodReplacement::Immediate {
+        spawn_food(ctx, 1);
+    }
+}
+
+/// Nudge each pellet within `FOOD_MAGNET_RANGE` of a player lighter than
+/// `FOOD_MAGNET_MASS` a step toward the closest such player's main cell.
+/// Heavier players attract nothing. Does nothing unless `food_magnet` is on.
+#[spacetimedb::reducer]
+pub fn attract_food(ctx: &ReducerContext, _schedule: FoodMagnetSchedule) {
+    if is_paused(ctx) { return; }
+    if !config(ctx).food_magnet { return; }
+
+    // Closest small player per pellet: (distance, player x, player y)
+    let mut pulls: HashMap<u64, (f32, f32, f32)> = HashMap::new();
+    let players: Vec<Player> = ctx.db.player().iter().collect();
+    for player in players {
+        let total = total_mass(ctx, player.identity);
+        if total >= FOOD_MAGNET_MASS { continue; }
+        let nearby = entities_near(ctx, player.x, player.y, player.radius + FOOD_MAGNET_RANGE);
+        for food in nearby_food(ctx, &nearby) {
+            let Some(dist) = magnet_pull(&player, total, &food) else { continue; };
+            let closer = pulls.get(&food.id).is_none_or(|&(best, _, _)| dist < best);
+            if closer {
+                pulls.insert(food.id, (dist, player.x, player.y));
+            }
+        }
+    }
+
+    for (food_id, (_, to_x, to_y)) in pulls {
+        let Some(food) = ctx.db.food_pellet().id().find(food_id) else { continue; };
+        let (x, y) = capped_move(food.x, food.y, to_x, to_y, FOOD_MAGNET_STEP);
+        grid_move(ctx, GridEntity::Food(food_id), (food.x, food.y), (x, y));
+        ctx.db.food_pellet().id().update(FoodPellet { x, y, ..food });
+    }
+}
+
+/// How far `food` is from `player` (whose cells weigh `total`) if the player's
+/// magnet pulls it: the player is under `FOOD_MAGNET_MASS` and the pellet within
+/// `FOOD_MAGNET_RANGE` of their edge.
+fn magnet_pull(player: &Player, total: f32, food: &FoodPellet) -> Option<f32> {
+    if total >= FOOD_MAGNET_MASS { return None; }
+    let (dx, dy) = (player.x - food.x, player.y - food.y);
+    let dist = (dx * dx + dy * dy).sqrt();
+    (dist <= player.radius + FOOD_MAGNET_RANGE).then_some(dist)
+}
+
+// ---------------------------------------------------------------------------
+// Decay (scheduled)
+// ---------------------------------------------------------------------------
+
+/// Shrink every cell above its floor. Skipped while fewer than two humans are
+/// online, so a lone player (bots aside) can practice without losing mass.
+#[spacetimedb::reducer]
+pub fn decay_mass(_ctx: &ReducerContext, _schedule: MassDecaySchedule) {
+    if is_paused(_ctx) { return; }
+    let is_bot = |identity| _ctx.db.bot().identity().find(identity).is_some();
+    if !decay_applies(_ctx.db.player().iter(), is_bot) { return; }
+
+    let cfg = config(_ctx);
+    for player in _ctx.db.player().iter() {
+        if player.mass > cfg.initial_mass {
+            let new_mass = decayed_mass(&cfg, player.mass).max(cfg.initial_mass);
+            _ctx.db.player().identity().update(Player {
+                mass: new_mass,
+                radius: cfg.mass_to_radius(new_mass),
+                ..player
+            });
+        }
+    }
+    // Also decay split cells (floor: the smallest mass a split can produce)
+    let min_cell_mass = cfg.min_cell_mass;
+    for cell in _ctx.db.player_cell().iter() {
+        if cell.mass > min_cell_mass {
+            let new_mass = decayed_mass(&cfg, cell.mass).max(min_cell_mass);
+            _ctx.db.player_cell().cell_id().update(PlayerCell {
+                mass: new_mass,
+                radius: cfg.mass_to_radius(new_mass),
+                ..cell
+            });
+        }
+    }
+    refresh_leaderboard(_ctx);
+    refresh_view_radii(_ctx);
+    let teams: Vec<u32> = _ctx.db.team_score().iter().map(|t| t.team_id).collect();
+    for team_id in teams {
+        refresh_team_score(_ctx, team_id);
+    }
+}
+
+/// Decay only runs while at least two humans are connected, so a lone player
+/// (or one playing against bots) keeps their mass.
+fn decay_applies(players: impl IntoIterator<Item = Player>, is_bot: impl Fn(Identity) -> bool) -> bool {
+    players.into_iter()
+        .filter(|p| p.disconnected_at.is_none() && !is_bot(p.identity))
+        .take(2)
+        .count() >= 2
+}
+
+/// Replace the decay schedule with one repeating at the configured interval.
+fn schedule_decay(ctx: &ReducerContext) {
+    let ids: Vec<u64> = ctx.db.mass_decay_schedule().iter().map(|s| s.scheduled_id).collect();
+    for id in ids {
+        ctx.db.mass_decay_schedule().scheduled_id().delete(id);
+    }
+    let interval = TimeDuration::from_micros(config(ctx).decay_interval_micros);
+    ctx.db.mass_decay_schedule().insert(MassDecaySchedule {
+        scheduled_id: 0,
+        scheduled_at: interval.into(),
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Mass audit (scheduled)
+// ---------------------------------------------------------------------------
+
+/// Sum the mass held by players, split cells, food and ejected pellets, and
+/// record it with the change since the last audit. Keeps `MAX_MASS_AUDITS` rows.
+#[spacetimedb::reducer]
+pub fn audit_mass(ctx: &ReducerContext, _schedule: MassAuditSchedule) {
+    if is_paused(ctx) { return; }
+    let player_mass: f32 = ctx.db.player().iter().map(|p| p.mass).sum();
+    let cell_mass: f32 = ctx.db.player_cell().iter().map(|c| c.mass).sum();
+    let food_mass: f32 = ctx.db.food_pellet().iter().map(|f| f.mass).sum();
+    let ejected_mass: f32 = ctx.db.ejected_mass().iter().map(|em| em.mass).sum();
+
+    let mut ids: Vec<u64> = ctx.db.mass_audit().iter().map(|a| a.id).collect();
+    ids.sort_unstable();
+    let previous = ids.last().and_then(|&id| ctx.db.mass_audit().id().find(id));
+    ctx.db.mass_audit().insert(mass_audit(
+        [player_mass, cell_mass, food_mass, ejected_mass],
+        previous.as_ref(),
+        ctx.timestamp,
+    ));
+
+    // Drop the oldest so at most `MAX_MASS_AUDITS` remain, counting the new row
+    let excess = (ids.len() + 1).saturating_sub(MAX_MASS_AUDITS);
+    for id in &ids[..excess] {
+        ctx.db.mass_audit().id().delete(*id);
+    }
+}
+
+/// Audit row for the given `[player, cell, food, ejected]` mass sums, with the
+/// delta from `previous` (zero for the first audit).
+fn mass_audit(
+    [player_mass, cell_mass, food_mass, ejected_mass]: [f32; 4],
+    previous: Option<&MassAudit>,
+    now: Timestamp,
+) -> MassAudit {
+    let total = player_mass + cell_mass + food_mass + ejected_mass;
+    MassAudit {
+        id: 0,
+        audited_at: now,
+        player_mass,
+        cell_mass,
+        food_mass,
+        ejected_mass,
+        total,
+        delta: previous.map_or(0.0, |a| total - a.total),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Eject mass
+// ---------------------------------------------------------------------------
+
+/// Shed `EJECT_MASS_FRACTION` of the main cell's mass (at least `eject_mass_amount`)
+/// as a pellet flung along `(dir_x, dir_y)`, pushing the cell backward with
+/// `EJECT_IMPULSE` that `update_split_motion` coasts out.
+#[spacetimedb::reducer]
+pub fn eject_mass(ctx: &ReducerContext, dir_x: f32, dir_y: f32) {
+    if is_paused(ctx) { return; }
+    if !all_finite(&[dir_x, dir_y]) { return; }
+    let identity = ctx.sender();
+    if !check_rate(ctx, RateLimitedAction::Eject, EJECT_COOLDOWN_MICROS) { return; }
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+
+    let cfg = config(ctx);
+    let amount = (player.mass * EJECT_MASS_FRACTION).max(cfg.eject_mass_amount);
+    if player.mass <= cfg.initial_mass + amount { return; }
+
+    let Some((nx, ny)) = eject_direction(&cfg, (dir_x, dir_y), &mut ctx.rng()) else { return; };
+    let pellet = launch_pellet(&cfg, &player, amount, (nx, ny), ctx.timestamp);
+
+    let new_mass = player.mass - amount;
+    let (vx, vy) = recoil((player.vx, player.vy), (nx, ny));
+    ctx.db.player().identity().update(Player {
+        mass: new_mass,
+        radius: cfg.mass_to_radius(new_mass),
+        vx,
+        vy,
+        last_active_at: ctx.timestamp,
+        ..player
+    });
+
+    if ctx.db.ejected_mass().count() >= MAX_EJECTED {
+        if let Some(oldest) = oldest_eject(ctx.db.ejected_mass().iter()) {
+            remove_ejected_mass(ctx, &oldest);
+        }
+    }
+    let em = ctx.db.ejected_mass().insert(pellet);
+    grid_insert(ctx, GridEntity::EjectedMass(em.id), em.x, em.y);
+    with_stats(ctx, identity, |stats| stats.mass_ejected += amount);
+    emit_event(ctx, GameEventKind::Eject, Some(identity), mass_payload(amount));
+    mass_changed(ctx, identity);
+}
+
+/// Main-cell velocity `v` after ejecting along unit `(nx, ny)`: pushed back by
+/// `EJECT_IMPULSE`, with the accumulated speed capped at `MAX_EJECT_IMPULSE`.
+fn recoil(v: (f32, f32), (nx, ny): (f32, f32)) -> (f32, f32) {
+    let (vx, vy) = (v.0 - nx * EJECT_IMPULSE, v.1 - ny * EJECT_IMPULSE);
+    let speed = (vx * vx + vy * vy).sqrt();
+    if speed > MAX_EJECT_IMPULSE {
+        return (vx * MAX_EJECT_IMPULSE / speed, vy * MAX_EJECT_IMPULSE / speed);
+    }
+    (vx, vy)
+}
+
+/// Unit vector to eject along: the cursor direction `(dir_x, dir_y)` turned by a
+/// random angle within `eject_spread` radians, so held ejects fan out. `None`
+/// for a zero-length direction.
+fn eject_direction(cfg: &GameConfig, (dir_x, dir_y): (f32, f32), rng: &mut impl Rng) -> Option<(f32, f32)> {
+    let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+    if len < 0.001 { return None; }
+    let angle = dir_y.atan2(dir_x) + rng.gen_range(-cfg.eject_spread..=cfg.eject_spread);
+    let (ny, nx) = angle.sin_cos();
+    Some((nx, ny))
+}
+
+/// The pellet evicted when the world is at `MAX_EJECTED`: the earliest
+/// ejected, by id among pellets ejected in the same tick.
+fn oldest_eject(pellets: impl Iterator<Item = EjectedMass>) -> Option<EjectedMass> {
+    pellets.min_by_key(|em| (em.ejected_at, em.id))
+}
+
+/// A pellet of `amount` shot by `player` along the unit vector `(nx, ny)` at
+/// `now`, in the player's color. It leaves from the cell's edge fast enough to
+/// coast to about `eject_distance` radii from the cell's center.
+fn launch_pellet(cfg: &GameConfig, player: &Player, amount: f32, (nx, ny): (f32, f32), now: Timestamp) -> EjectedMass {
+    let eject_dist = cfg.eject_distance * player.radius;
+    let radius = cfg.mass_to_radius(amount);
+    let start_offset = player.radius + radius;
+    let (x, y) = cfg.clamp_to_world(
+        player.x + nx * start_offset,
+        player.y + ny * start_offset,
+        20.0,
+    );
+    let launch_speed = (eject_dist - start_offset).max(0.0) * EJECT_FRICTION;
+    EjectedMass {
+        id: 0,
+        x,
+        y,
+        radius,
+        mass: amount,
+        ejected_at: now,
+        vx: nx * launch_speed,
+        vy: ny * launch_speed,
+        color: player.color,
+        ejector: player.identity,
+    }
+}
+
+/// Move in-flight ejected mass along its velocity, applying friction until it rests.
+/// A pellet that hits a virus on the way is fed into it.
+#[spacetimedb::reducer]
+pub fn update_ejected_motion(ctx: &ReducerContext, _schedule: EjectedMotionSchedule) {
+    if is_paused(ctx) { return; }
+    let cfg = config(ctx);
+    let moving: Vec<EjectedMass> = ctx.db.ejected_mass().iter()
+        .filter(|em| em.vx != 0.0 || em.vy != 0.0)
+        .collect();
+    for em in moving {
+        let ((x, y), (vx, vy)) = drift_step(&cfg, &em);
+        let (id, radius) = (em.id, em.radius);
+        grid_move(ctx, GridEntity::EjectedMass(id), (em.x, em.y), (x, y));
+        ctx.db.ejected_mass().id().update(EjectedMass { x, y, vx, vy, ..em });
+
+        let hit = ctx.db.virus().iter().find(|v| touches_virus(v, (x, y), radius));
+        if let Some(virus) = hit {
+            try_feed_virus(ctx, virus.id, id);
+        }
+    }
+}
+
+/// Position and velocity of `em` after one motion tick: it coasts inside the
+/// world under `EJECT_FRICTION` and comes to rest below `EJECT_REST_SPEED`.
+fn drift_step(cfg: &GameConfig, em: &EjectedMass) -> ((f32, f32), (f32, f32)) {
+    let dt = MOTION_TICK_MICROS as f32 / 1_000_000.0;
+    let damping = (-EJECT_FRICTION * dt).exp();
+    let (x, y) = cfg.clamp_to_world(em.x + em.vx * dt, em.y + em.vy * dt, em.radius);
+    let (vx, vy) = (em.vx * damping, em.vy * damping);
+    if (vx * vx + vy * vy).sqrt() < EJECT_REST_SPEED {
+        return ((x, y), (0.0, 0.0));
+    }
+    ((x, y), (vx, vy))
+}
+
+/// Delete ejected mass that nobody ate within `EJECTED_MASS_LIFETIME_MICROS`.
+#[spacetimedb::reducer]
+pub fn expire_ejected_mass(ctx: &ReducerContext, _schedule: EjectedMassExpirySchedule) {
+    if is_paused(ctx) { return; }
+    let expired: Vec<EjectedMass> = ctx.db.ejected_mass().iter()
+        .filter(|em| is_expired(em, ctx.timestamp))
+        .collect();
+    for em in expired {
+        remove_ejected_mass(ctx, &em);
+    }
+}
+
+/// Whether `em` has lain uneaten for `EJECTED_MASS_LIFETIME_MICROS`.
+fn is_expired(em: &EjectedMass, now: Timestamp) -> bool {
+    em.ejected_at + TimeDuration::from_micros(EJECTED_MASS_LIFETIME_MICROS) <= now
+}
+
+// ---------------------------------------------------------------------------
+// Mass transfer
+// ---------------------------------------------------------------------------
+
+/// Give `amount` mass from the caller's main cell to an overlapping teammate.
+/// The giver must keep at least `initial_mass`; only as much as the recipient
+/// can hold under `max_mass` is transferred.
+#[spacetimedb::reducer]
+pub fn transfer_mass(ctx: &ReducerContext, target_identity: Identity, amount: f32) {
+    if is_paused(ctx) { return; }
+    let giver_id = ctx.sender();
+    if giver_id == target_identity { return; }
+    if !amount.is_finite() || amount <= 0.0 { return; }
+
+    let Some(giver) = ctx.db.player().identity().find(giver_id) else { return; };
+    let Some(target) = ctx.db.player().identity().find(target_identity) else { return; };
+    let cfg = config(ctx);
+    let Some(amount) = transferable(&cfg, &giver, &target, amount) else { return; };
+
+    let giver_mass = giver.mass - amount;
+    ctx.db.player().identity().update(Player {
+        mass: giver_mass,
+        radius: cfg.mass_to_radius(giver_mass),
+        ..giver
+    });
+    let target_mass = target.mass + amount;
+    grow_player(ctx, target, target_mass);
+    mass_changed(ctx, giver_id);
+    mass_changed(ctx, target_identity);
+}
+
+/// How much of `amount` `giver` can hand to `target`: they must be touching
+/// teammates, the giver keeps `initial_mass`, and the target stays within `max_mass`.
+fn transferable(cfg: &GameConfig, giver: &Player, target: &Player, amount: f32) -> Option<f32> {
+    if !same_team(giver, target) { return None; }
+    if giver.mass - amount < cfg.initial_mass { return None; }
+    if !within_reach(giver.x, giver.y, target.x, target.y, giver.radius + target.radius) { return None; }
+    let amount = amount.min(cfg.max_mass - target.mass);
+    (amount > 0.0).then_some(amount)
+}
+
+// ---------------------------------------------------------------------------
+// Split / merge
+// ---------------------------------------------------------------------------
+
+/// Split every one of the player's cells in half. Each new half is inserted as a
+/// PlayerCell row, enabling all cells to move independently toward the cursor.
+/// A cell splits only if it is at least `min_split_mass` and both halves would be
+/// at least `min_cell_mass`; the total never exceeds `MAX_CELLS`. A player already
+/// at the cap (main cell plus split cells) is turned away before anything else,
+/// so the attempt doesn't use up their split cooldown.
+#[spacetimedb::reducer]
+pub fn split_cell(ctx: &ReducerContext, dir_x: f32, dir_y: f32) {
+    if is_paused(ctx) { return; }
+    if !all_finite(&[dir_x, dir_y]) { return; }
+    let identity = ctx.sender();
+    let cells: Vec<PlayerCell> = ctx.db.player_cell().player_identity().filter(identity).collect();
+    let initial_count = 1 + cells.len();
+    if initial_count >= MAX_CELLS { return; }
+    if !check_rate(ctx, RateLimitedAction::Split, SPLIT_COOLDOWN_MICROS) { return; }
+    mark_active(ctx, identity);
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+
+    let mut cell_count = initial_count;
+
+    let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+    if len < 0.001 { return; }
+    let nx = dir_x / len;
+    let ny = dir_y / len;
+
+    let cfg = config(ctx);
+    let masses: Vec<f32> = std::iter::once(player.mass).chain(cells.iter().map(|c| c.mass)).collect();
+    let splitting = cells_to_split(&cfg, &masses);
+    if splitting.first() == Some(&0) {
+        let half_mass = player.mass / 2.0;
+        let (x, y) = (player.x, player.y);
+        ctx.db.player().identity().update(Player {
+            mass: half_mass,
+            radius: cfg.mass_to_radius(half_mass),
+            ..player
+        });
+        insert_split_cell(ctx, identity, x, y, half_mass, nx, ny);
+        cell_count += 1;
+    }
+
+    for (i, cell) in cells.into_iter().enumerate() {
+        if !splitting.contains(&(i + 1)) { continue; }
+        let half_mass = cell.mass / 2.0;
+        let (x, y) = (cell.x, cell.y);
+        ctx.db.player_cell().cell_id().update(PlayerCell {
+            mass: half_mass,
+            radius: cfg.mass_to_radius(half_mass),
+            ..cell
+        });
+        insert_split_cell(ctx, identity, x, y, half_mass, nx, ny);
+        cell_count += 1;
+    }
+
+    if cell_count > initial_count {
+        with_stats(ctx, identity, |stats| stats.times_split += 1);
+        emit_event(
+            ctx,
+            GameEventKind::Split,
+            Some(identity),
+            format!("{{\"cells\":{}}}", cell_count),
+        );
+    }
+}
+
+/// Which of a player's cells split, given their masses with the main cell
+/// first: the main cell, then each split cell in turn, while the mass allows
+/// it and until the player would exceed `MAX_CELLS`.
+fn cells_to_split(cfg: &GameConfig, masses: &[f32]) -> Vec<usize> {
+    let mut count = masses.len();
+    let mut splitting = Vec::new();
+    for (i, &mass) in masses.iter().enumerate() {
+        if count >= MAX_CELLS { break; }
+        if !cfg.can_split(mass) { continue; }
+        splitting.push(i);
+        count += 1;
+    }
+    splitting
+}
+
+/// Glide freshly split cells along their launch velocity, and main cells along
+/// their eject impulse, until friction stops them.
+#[spacetimedb::reducer]
+pub fn update_split_motion(ctx: &ReducerContext, _schedule: SplitMotionSchedule) {
+    if is_paused(ctx) { return; }
+    let cfg = config(ctx);
+    let dt = MOTION_TICK_MICROS as f32 / 1_000_000.0;
+    let damping = (-EJECT_FRICTION * dt).exp();
+    let obstacles = all_obstacles(ctx);
+    let moving: Vec<PlayerCell> = ctx.db.player_cell().iter()
+        .filter(|c| c.vx != 0.0 || c.vy != 0.0)
+        .collect();
+    for cell in moving {
+        let to = cfg.clamp_to_world(cell.x + cell.vx * dt, cell.y + cell.vy * dt, cell.radius);
+        let (x, y) = obstructed_move(&obstacles, (cell.x, cell.y), to, cell.radius);
+        let (vx, vy) = damped_velocity((cell.vx, cell.vy), damping, to, (x, y));
+        grid_move(ctx, GridEntity::Cell(cell.cell_id), (cell.x, cell.y), (x, y));
+        ctx.db.player_cell().cell_id().update(PlayerCell { x, y, vx, vy, ..cell });
+    }
+
+    let pushed: Vec<Player> = ctx.db.player().iter()
+        .filter(|p| p.vx != 0.0 || p.vy != 0.0)
+        .collect();
+    for player in pushed {
+        let to = cfg.clamp_to_world(player.x + player.vx * dt, player.y + player.vy * dt, player.radius);
+        let (x, y) = obstructed_move(&obstacles, (player.x, player.y), to, player.radius);
+        let (vx, vy) = damped_velocity((player.vx, player.vy), damping, to, (x, y));
+        grid_move(ctx, GridEntity::Player(player.identity), (player.x, player.y), (x, y));
+        ctx.db.player().identity().update(Player { x, y, vx, vy, ..player });
+    }
+}
+
+/// Velocity after one motion tick: damped, zeroed along any axis a wall
+/// stopped (`reached` short of `intended`), and zeroed entirely once slow.
+fn damped_velocity(v: (f32, f32), damping: f32, intended: (f32, f32), reached: (f32, f32)) -> (f32, f32) {
+    let vx = if reached.0 == intended.0 { v.0 * damping } else { 0.0 };
+    let vy = if reached.1 == intended.1 { v.1 * damping } else { 0.0 };
+    if (vx * vx + vy * vy).sqrt() < EJECT_REST_SPEED {
+        return (0.0, 0.0);
+    }
+    (vx, vy)
+}
+
+/// Merge every split cell whose timer has elapsed back into the main cell, then
+/// reschedule for the next pending one. The merged cell lands on the
+/// mass-weighted centroid of the cells that joined, so mass doesn't jump back
+/// to wherever the main cell happened to be.
+#[spacetimedb::reducer]
+pub fn merge_split(ctx: &ReducerContext, schedule: SplitMergeSchedule) {
+    if let Some(retry_at) = paused_retry(&config(ctx), ctx.timestamp) {
+        ctx.db.split_merge_schedule().insert(SplitMergeSchedule {
+            scheduled_id: 0,
+            scheduled_at: retry_at.into(),
+            ..schedule
+        });
+        return;
+    }
+    let identity = schedule.player_identity;
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    let due: Vec<PlayerCell> = ctx.db.player_cell().player_identity().filter(identity)
+        .filter(|c| c.merge_at <= ctx.timestamp)
+        .collect();
+
+    let ((x, y), merged_mass) = merged_centroid(&player, &due);
+    for cell in &due {
+        remove_cell(ctx, cell);
+    }
+    grid_move(ctx, GridEntity::Player(identity), (player.x, player.y), (x, y));
+    grow_player(ctx, Player { x, y, ..player }, merged_mass);
+    schedule_merge(ctx, identity);
+}
+
+/// Mass-weighted centroid and total mass of `main` with the `cells` merging
+/// into it; `main`'s own position if nothing weighs anything.
+fn merged_centroid(main: &Player, cells: &[PlayerCell]) -> ((f32, f32), f32) {
+    let mut merged_mass = main.mass;
+    let mut weighted_x = main.x * main.mass;
+    let mut weighted_y = main.y * main.mass;
+    for cell in cells {
+        merged_mass += cell.mass;
+        weighted_x += cell.x * cell.mass;
+        weighted_y += cell.y * cell.mass;
+    }
+    if merged_mass <= 0.0 {
+        return ((main.x, main.y), merged_mass);
+    }
+    ((weighted_x / merged_mass, weighted_y / merged_mass), merged_mass)
+}
+
+// ---------------------------------------------------------------------------
+// Viruses
+// ---------------------------------------------------------------------------
+
+/// Deprecated: viruses are hit by `resolve_collisions`. Kept as a no-op so
+/// existing clients don't error.
+#[spacetimedb::reducer]
+pub fn eat_virus(_ctx: &ReducerContext, _virus_id: u64) {}
+
+/// Run into a virus: if the player is heavier than the virus and covers its center,
+/// the virus is destroyed and the player bursts into up to `VIRUS_SPLIT_PIECES`
+/// cells flung out in all directions. The burst never takes the player past
+/// `MAX_CELLS` or makes a fragment lighter than `MIN_FRAGMENT_MASS`; whatever can't
+/// be split off stays on the main cell. A player already at the cap simply eats
+/// the virus. Smaller players are unaffected.
+fn try_eat_virus(ctx: &ReducerContext, identity: Identity, virus_id: u64) {
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    let Some(virus) = ctx.db.virus().id().find(virus_id) else { return; };
+
+    if player.mass <= virus.mass { return; }
+    if !within_reach(player.x, player.y, virus.x, virus.y, player.radius) { return; }
+
+    remove_virus(ctx, &virus);
+
+    let cell_count = 1 + ctx.db.player_cell().player_identity().filter(identity).count();
+    let (fragments, piece_mass) = virus_burst(player.mass, cell_count);
+    if fragments == 0 {
+        let new_mass = player.mass + virus.mass;
+        grow_player(ctx, player, new_mass);
+        mass_changed(ctx, identity);
+        return;
+    }
+
+    let main_mass = player.mass - piece_mass * fragments as f32;
+    let (x, y) = (player.x, player.y);
+    ctx.db.player().identity().update(Player {
+        mass: main_mass,
+        radius: config(ctx).mass_to_radius(main_mass),
+        ..player
+    });
+
+    for i in 0..fragments {
+        let angle = i as f32 * std::f32::consts::TAU / fragments as f32;
+        insert_split_cell(ctx, identity, x, y, piece_mass, angle.cos(), angle.sin());
+    }
+}
+
+/// How a main cell of `mass` bursts on a virus when its owner already has
+/// `cell_count` cells: the number of fragments flung off and the mass of each.
+/// Each fragment takes one share; the main cell keeps the remainder.
+fn virus_burst(mass: f32, cell_count: usize) -> (usize, f32) {
+    let slots = MAX_CELLS.saturating_sub(cell_count);
+    let viable = ((mass / MIN_FRAGMENT_MASS) as usize).max(1);
+    let fragments = (VIRUS_SPLIT_PIECES - 1).min(slots).min(viable - 1);
+    (fragments, mass / VIRUS_SPLIT_PIECES.min(viable) as f32)
+}
+
+/// Feed an ejected mass pellet that overlaps a virus into it. Every
+/// `VIRUS_FEED_LIMIT` feeds the virus shrinks back to `VIRUS_MASS` and shoots a
+/// new virus onward in the direction the last pellet was travelling.
+#[spacetimedb::reducer]
+pub fn feed_virus(ctx: &ReducerContext, virus_id: u64, mass_id: u64) {
+    if is_paused(ctx) { return; }
+    try_feed_virus(ctx, virus_id, mass_id);
+}
+
+/// Feed pellet `mass_id` into virus `virus_id` if they overlap. The pellet is
+/// deleted as it is counted, so it can only ever feed once.
+fn try_feed_virus(ctx: &ReducerContext, virus_id: u64, mass_id: u64) {
+    let Some(virus) = ctx.db.virus().id().find(virus_id) else { return; };
+    let Some(em) = ctx.db.ejected_mass().id().find(mass_id) else { return; };
+
+    if !touches_virus(&virus, (em.x, em.y), em.radius) { return; }
+
+    remove_ejected_mass(ctx, &em);
+
+    let cfg = config(ctx);
+    if virus.feed_count + 1 < VIRUS_FEED_LIMIT {
+        ctx.db.virus().id().update(fed_virus(&cfg, virus, em.mass));
+        return;
+    }
+
+    let (shot_x, shot_y) = shot_position(&cfg, &virus, (em.x, em.y));
+
+    ctx.db.virus().id().update(Virus {
+        mass: VIRUS_MASS,
+        radius: cfg.mass_to_radius(VIRUS_MASS),
+        feed_count: 0,
+        ..virus
+    });
+    let shot = ctx.db.virus().insert(Virus {
+        id: 0,
+        x: shot_x,
+        y: shot_y,
+        radius: cfg.mass_to_radius(VIRUS_MASS),
+        mass: VIRUS_MASS,
+        feed_count: 0,
+    });
+    grid_insert(ctx, GridEntity::Virus(shot.id), shot.x, shot.y);
+}
+
+/// Whether a pellet of `radius` at `(x, y)` overlaps `virus`.
+fn touches_virus(virus: &Virus, (x, y): (f32, f32), radius: f32) -> bool {
+    let (dx, dy) = (virus.x - x, virus.y - y);
+    let feed_dist = virus.radius + radius;
+    dx * dx + dy * dy <= feed_dist * feed_dist
+}
+
+/// `virus` after absorbing a pellet of `mass` short of `VIRUS_FEED_LIMIT`:
+/// one more feed counted and the mass added.
+fn fed_virus(cfg: &GameConfig, virus: Virus, mass: f32) -> Virus {
+    let new_mass = virus.mass + mass;
+    Virus {
+        mass: new_mass,
+        radius: cfg.mass_to_radius(new_mass),
+        feed_count: virus.feed_count + 1,
+        ..virus
+    }
+}
+
+/// Where a virus fed its last pellet from `fed_from` shoots its offspring:
+/// `VIRUS_SHOOT_DISTANCE` onward along the pellet's path toward the virus
+/// center, kept inside the world.
+fn shot_position(cfg: &GameConfig, virus: &Virus, fed_from: (f32, f32)) -> (f32, f32) {
+    let dx = virus.x - fed_from.0;
+    let dy = virus.y - fed_from.1;
+    let len = (dx * dx + dy * dy).sqrt();
+    let (nx, ny) = if len < 0.001 { (1.0, 0.0) } else { (dx / len, dy / len) };
+    cfg.clamp_to_world(
+        virus.x + nx * VIRUS_SHOOT_DISTANCE,
+        virus.y + ny * VIRUS_SHOOT_DISTANCE,
+        100.0,
+    )
+}
+
+/// Keep the virus population stable by respawning destroyed viruses.
+#[spacetimedb::reducer]
+pub fn respawn_viruses(ctx: &ReducerContext, _schedule: VirusSpawnSchedule) {
+    if is_paused(ctx) { return; }
+    let count = ctx.db.virus().count() as u32;
+    for _ in count..VIRUS_COUNT {
+        spawn_virus(ctx);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Power-ups
+// ---------------------------------------------------------------------------
+
+/// Pick up a power-up the caller's main cell overlaps, starting (or restarting)
+/// its timed effect.
+#[spacetimedb::reducer]
+pub fn eat_powerup(ctx: &ReducerContext, powerup_id: u64) {
+    if is_paused(ctx) { return; }
+    let identity = ctx.sender();
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    let Some(powerup) = ctx.db.power_up().id().find(powerup_id) else { return; };
+
+    let dx = powerup.x - player.x;
+    let dy = powerup.y - player.y;
+    let reach = player.radius + POWERUP_RADIUS;
+    if dx * dx + dy * dy > reach * reach { return; }
+
+    ctx.db.power_up().id().delete(powerup_id);
+    ctx.db.player().identity().update(with_powerup(player, powerup.kind, ctx.timestamp));
+}
+
+/// `player` after picking up a `kind` power-up at `now`.
+fn with_powerup(player: Player, kind: PowerUpKind, now: Timestamp) -> Player {
+    match kind {
+        PowerUpKind::SpeedBoost => Player {
+            boost_until: now + TimeDuration::from_micros(BOOST_DURATION_MICROS),
+            ..player
+        },
+        PowerUpKind::Shield => Player {
+            shield_until: now + TimeDuration::from_micros(SHIELD_DURATION_MICROS),
+            ..player
+        },
+    }
+}
+
+#[spacetimedb::reducer]
+pub fn respawn_powerups(ctx: &ReducerContext, _schedule: PowerUpSpawnSchedule) {
+    if is_paused(ctx) { return; }
+    let count = ctx.db.power_up().count() as u32;
+    for _ in count..POWERUP_COUNT {
+        spawn_powerup(ctx);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Portals
+// ---------------------------------------------------------------------------
+
+/// Place a portal pair. Both ends are clamped into the world. Admin only.
+#[spacetimedb::reducer]
+pub fn add_portal(ctx: &ReducerContext, entry_x: f32, entry_y: f32, exit_x: f32, exit_y: f32) {
+    if !is_admin(ctx) { return; }
+    if !all_finite(&[entry_x, entry_y, exit_x, exit_y]) { return; }
+    insert_portal(ctx, entry_x, entry_y, exit_x, exit_y);
+}
+
+/// Admin only.
+#[spacetimedb::reducer]
+pub fn remove_portal(ctx: &ReducerContext, portal_id: u64) {
+    if !is_admin(ctx) { return; }
+    ctx.db.portal().id().delete(portal_id);
+}
+
+/// Teleport every player whose main cell overlaps a portal end to the other end.
+/// Split cells travel with the main cell, keeping their relative offsets.
+#[spacetimedb::reducer]
+pub fn use_portals(ctx: &ReducerContext, _schedule: PortalSchedule) {
+    if is_paused(ctx) { return; }
+    let portals: Vec<Portal> = ctx.db.portal().iter().collect();
+    if portals.is_empty() { return; }
+
+    let cfg = config(ctx);
+    let obstacles = all_obstacles(ctx);
+    let ready: Vec<Player> = ctx.db.player().iter()
+        .filter(|p| p.teleport_ready_at <= ctx.timestamp)
+        .collect();
+    for player in ready {
+        let Some((to_x, to_y)) = portal_destination(&portals, &player) else { continue; };
+
+        let identity = player.identity;
+        let (x, y) = push_out_of_obstacles(&obstacles, to_x, to_y, player.radius);
+        let (x, y) = cfg.clamp_to_world(x, y, player.radius);
+        let (dx, dy) = (x - player.x, y - player.y);
+        let cells: Vec<PlayerCell> = ctx.db.player_cell().player_identity().filter(identity).collect();
+        for cell in cells {
+            let (cell_x, cell_y) = push_out_of_obstacles(&obstacles, cell.x + dx, cell.y + dy, cell.radius);
+            let (cell_x, cell_y) = cfg.clamp_to_world(cell_x, cell_y, cell.radius);
+            grid_move(ctx, GridEntity::Cell(cell.cell_id), (cell.x, cell.y), (cell_x, cell_y));
+            ctx.db.player_cell().cell_id().update(PlayerCell { x: cell_x, y: cell_y, ..cell });
+        }
+
+        grid_move(ctx, GridEntity::Player(identity), (player.x, player.y), (x, y));
+        ctx.db.player().identity().update(Player {
+            x,
+            y,
+            teleport_ready_at: ctx.timestamp + TimeDuration::from_micros(TELEPORT_COOLDOWN_MICROS),
+            ..player
+        });
+    }
+}
+
+/// Where `player` is sent by the first portal they touch: a player on either
+/// end comes out at the other.
+fn portal_destination(portals: &[Portal], player: &Player) -> Option<(f32, f32)> {
+    let touches = |x: f32, y: f32, r: f32| within_reach(player.x, player.y, x, y, player.radius + r);
+    portals.iter().find_map(|portal| {
+        if touches(portal.entry_x, portal.entry_y, portal.radius) {
+            Some((portal.exit_x, portal.exit_y))
+        } else if touches(portal.exit_x, portal.exit_y, portal.radius) {
+            Some((portal.entry_x, portal.entry_y))
+        } else {
+            None
+        }
+    })
+}
+
+fn insert_portal(ctx: &ReducerContext, entry_x: f32, entry_y: f32, exit_x: f32, exit_y: f32) {
+    ctx.db.portal().insert(portal_between(&config(ctx), (entry_x, entry_y), (exit_x, exit_y)));
+}
+
+/// A portal from `entry` to `exit`, both kept inside the world.
+fn portal_between(cfg: &GameConfig, (entry_x, entry_y): (f32, f32), (exit_x, exit_y): (f32, f32)) -> Portal {
+    let (entry_x, entry_y) = cfg.clamp_to_world(entry_x, entry_y, PORTAL_RADIUS);
+    let (exit_x, exit_y) = cfg.clamp_to_world(exit_x, exit_y, PORTAL_RADIUS);
+    Portal { id: 0, entry_x, entry_y, exit_x, exit_y, radius: PORTAL_RADIUS }
+}
+
+// ---------------------------------------------------------------------------
+// Obstacles
+// ---------------------------------------------------------------------------
+
+/// Place a wall with its top-left corner at `(x, y)`, pushing out any cells
+/// it lands on. Admin only.
+#[spacetimedb::reducer]
+pub fn add_obstacle(ctx: &ReducerContext, x: f32, y: f32, width: f32, height: f32) {
+    if !is_admin(ctx) { return; }
+    if !all_finite(&[x, y, width, height]) || width <= 0.0 || height <= 0.0 { return; }
+    let obstacle = ctx.db.obstacle().insert(Obstacle { id: 0, x, y, width, height });
+
+    let cfg = config(ctx);
+    let players: Vec<Player> = ctx.db.player().iter()
+        .filter(|p| obstacle.overlaps(p.x, p.y, p.radius))
+        .collect();
+    for player in players {
+        let (x, y) = obstacle.push_out(player.x, player.y, player.radius);
+        let (x, y) = cfg.clamp_to_world(x, y, player.radius);
+        grid_move(ctx, GridEntity::Player(player.identity), (player.x, player.y), (x, y));
+        ctx.db.player().identity().update(Player { x, y, ..player });
+    }
+    let cells: Vec<PlayerCell> = ctx.db.player_cell().iter()
+        .filter(|c| obstacle.overlaps(c.x, c.y, c.radius))
+        .collect();
+    for cell in cells {
+        let (x, y) = obstacle.push_out(cell.x, cell.y, cell.radius);
+        let (x, y) = cfg.clamp_to_world(x, y, cell.radius);
+        grid_move(ctx, GridEntity::Cell(cell.cell_id), (cell.x, cell.y), (x, y));
+        ctx.db.player_cell().cell_id().update(PlayerCell { x, y, ..cell });
+    }
+}
+
+#[spacetimedb::reducer]
+pub fn remove_obstacle(ctx: &ReducerContext, obstacle_id: u64) {
+    if !is_admin(ctx) { return; }
+    ctx.db.obstacle().id().delete(obstacle_id);
+}
+
+/// Every wall, for the helpers below; there are only ever a handful.
+fn all_obstacles(ctx: &ReducerContext) -> Vec<Obstacle> {
+    ctx.db.obstacle().iter().collect()
+}
+
+/// Where a cell of `radius` ends up moving from `from` toward `to`: `to` if
+/// the whole path is clear, else sliding along whichever axis is clear, else
+/// staying put. A cell already stuck in a wall is pushed out rather than held
+/// in place.
+fn obstructed_move(obstacles: &[Obstacle], from: (f32, f32), to: (f32, f32), radius: f32) -> (f32, f32) {
+    [to, (to.0, from.1), (from.0, to.1)]
+        .into_iter()
+        .find(|&dest| path_clear(obstacles, from, dest, radius))
+        .unwrap_or_else(|| push_out_of_obstacles(obstacles, from.0, from.1, radius))
+}
+
+/// Whether a cell can sweep from `from` to `to` without touching a wall. A wall
+/// the cell already overlaps only has to be clear of at `to`, so it can escape.
+fn path_clear(obstacles: &[Obstacle], from: (f32, f32), to: (f32, f32), radius: f32) -> bool {
+    obstacles.iter().all(|o| {
+        if o.overlaps(from.0, from.1, radius) {
+            !o.overlaps(to.0, to.1, radius)
+        } else {
+            !o.blocks_path(from, to, radius)
+        }
+    })
+}
+
+/// Squared distance from `p` to the segment `a`-`b`.
+fn segment_dist_sq(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (a.0 + dx * t - p.0, a.1 + dy * t - p.1);
+    cx * cx + cy * cy
+}
+
+/// Push a cell of `radius` at `(x, y)` out of every wall it overlaps.
+fn push_out_of_obstacles(obstacles: &[Obstacle], x: f32, y: f32, radius: f32) -> (f32, f32) {
+    obstacles.iter().fold((x, y), |(x, y), o| o.push_out(x, y, radius))
+}
+
+// ---------------------------------------------------------------------------
+// Bots (scheduled)
+// ---------------------------------------------------------------------------
+
+/// Advance every bot one step: flee the nearest larger player in sight, otherwise
+/// chase the nearest food. Eaten bots respawn.
+#[spacetimedb::reducer]
+pub fn tick_bots(ctx: &ReducerContext, _schedule: BotTickSchedule) {
+    if is_paused(ctx) { return; }
+    sync_bots(ctx);
+    let bots: Vec<Bot> = ctx.db.bot().iter().collect();
+    for bot in bots {
+        match ctx.db.player().identity().find(bot.identity) {
+            Some(player) => step_bot(ctx, player),
+            None => {
+                let _ = spawn(ctx, bot.identity, format!("Bot {}", bot.index + 1), 0, None);
+            }
+        }
+    }
+}
+
+fn step_bot(ctx: &ReducerContext, bot: Player) {
+    let identity = bot.identity;
+    let nearby = entities_near(ctx, bot.x, bot.y, BOT_SIGHT_RADIUS);
+    let target = bot_target(
+        &bot,
+        nearby_players(ctx, &nearby),
+        nearby_food(ctx, &nearby).map(|f| (f.x, f.y)),
+    );
+    let Some((tx, ty)) = target else { return; };
+
+    let cfg = config(ctx);
+    let step = cell_speed(&cfg, bot.mass) * BOT_TICK_MICROS as f32 / 1_000_000.0;
+    let (x, y) = capped_move(bot.x, bot.y, tx, ty, step);
+    let to = cfg.clamp_to_world(x, y, bot.radius);
+    let (x, y) = obstructed_move(&all_obstacles(ctx), (bot.x, bot.y), to, bot.radius);
+    grid_move(ctx, GridEntity::Player(identity), (bot.x, bot.y), (x, y));
+    ctx.db.player().identity().update(Player {
+        x,
+        y,
+        last_move_micros: ctx.timestamp.to_micros_since_unix_epoch(),
+        ..bot
+    });
+}
+
+/// Where `bot` heads next: directly away from the closest player in sight that
+/// could eat it, otherwise toward the closest food in sight.
+fn bot_target(
+    bot: &Player,
+    players: impl Iterator<Item = Player>,
+    food: impl Iterator<Item = (f32, f32)>,
+) -> Option<(f32, f32)> {
+    let sight_sq = BOT_SIGHT_RADIUS * BOT_SIGHT_RADIUS;
+    let dist_sq = |x: f32, y: f32| (x - bot.x) * (x - bot.x) + (y - bot.y) * (y - bot.y);
+
+    let threat = players
+        .filter(|p| p.identity != bot.identity && can_absorb(p.mass, bot.mass) && !same_team(p, bot))
+        .map(|p| (dist_sq(p.x, p.y), p))
+        .filter(|(d, _)| *d <= sight_sq)
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+
+    match threat {
+        // Run directly away from the threat
+        Some((_, p)) => Some((2.0 * bot.x - p.x, 2.0 * bot.y - p.y)),
+        None => food
+            .map(|(x, y)| (dist_sq(x, y), (x, y)))
+            .filter(|(d, _)| *d <= sight_sq)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, f)| f),
+    }
+}
+
+/// Add or remove bot rows (and their players) so the count matches `GameConfig.bot_count`.
+fn sync_bots(ctx: &ReducerContext) {
+    let bot_count = config(ctx).bot_count;
+    let surplus: Vec<Identity> = ctx.db.bot().iter()
+        .filter(|b| b.index >= bot_count)
+        .map(|b| b.identity)
+        .collect();
+    for identity in surplus {
+        remove_player(ctx, identity);
+        ctx.db.bot().identity().delete(identity);
+    }
+
+    for index in 0..bot_count {
+        let identity = bot_identity(index);
+        if ctx.db.bot().identity().find(identity).is_none() {
+            ctx.db.bot().insert(Bot { identity, index });
+            let _ = spawn(ctx, identity, format!("Bot {}", index + 1), 0, None);
+        }
+    }
+}
+
+/// Stable synthetic identity for the bot in slot `index`.
+fn bot_identity(index: u32) -> Identity {
+    let mut bytes = [0u8; 32];
+    bytes[..4].copy_from_slice(b"BOT!");
+    bytes[28..].copy_from_slice(&index.to_le_bytes());
+    Identity::from_byte_array(bytes)
+}
+
+// ---------------------------------------------------------------------------
+// Rounds
+// ---------------------------------------------------------------------------
+
+/// Switch to timed matches: start a round lasting `ROUND_DURATION_MICROS`, after
+/// which a new one starts automatically. Ignored while a round is running. Admin only.
+#[spacetimedb::reducer]
+pub fn start_round(ctx: &ReducerContext) {
+    if !is_admin(ctx) { return; }
+    if ctx.db.game_round().iter().any(|r| r.winner_identity.is_none() && r.ends_at > ctx.timestamp) {
+        return;
+    }
+    begin_round(ctx);
+}
+
+/// Record the heaviest player as the round's winner, reset everyone to
+/// `initial_mass`, and start the next round.
+#[spacetimedb::reducer]
+pub fn end_round(ctx: &ReducerContext, schedule: RoundEndSchedule) {
+    if let Some(retry_at) = paused_retry(&config(ctx), ctx.timestamp) {
+        ctx.db.round_end_schedule().insert(RoundEndSchedule {
+            scheduled_id: 0,
+            scheduled_at: retry_at.into(),
+            ..schedule
+        });
+        return;
+    }
+    let Some(round) = ctx.db.game_round().round_id().find(schedule.round_id) else { return; };
+    if round.winner_identity.is_some() { return; }
+
+    let winner = round_winner(ctx.db.player().iter().map(|p| (total_mass(ctx, p.identity), p)));
+    let winner_identity = winner.as_ref().map(|p| p.identity);
+    let winner_name = winner.map(|p| p.name);
+    emit_event(
+        ctx,
+        GameEventKind::RoundEnd,
+        winner_identity,
+        round_end_payload(round.round_id, winner_name.as_deref()),
+    );
+    ctx.db.game_round().round_id().update(GameRound { winner_identity, winner_name, ..round });
+
+    let cfg = config(ctx);
+    let initial_mass = cfg.initial_mass;
+    let players: Vec<Player> = ctx.db.player().iter().collect();
+    for player in players {
+        let identity = player.identity;
+        delete_player_cells(ctx, identity);
+        delete_merge_schedules(ctx, identity);
+        ctx.db.player().identity().update(Player {
+            mass: initial_mass,
+            radius: cfg.mass_to_radius(initial_mass),
+            ..player
+        });
+    }
+    refresh_leaderboard(ctx);
+    refresh_view_radii(ctx);
+    let teams: Vec<u32> = ctx.db.team_score().iter().map(|t| t.team_id).collect();
+    for team_id in teams {
+        refresh_team_score(ctx, team_id);
+    }
+
+    begin_round(ctx);
+}
+
+/// The player with the most total mass, from `(total_mass, player)` pairs.
+fn round_winner(players: impl IntoIterator<Item = (f32, Player)>) -> Option<Player> {
+    players.into_iter()
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, p)| p)
+}
+
+/// JSON payload of a `RoundEnd` event; `winner` is `None` for an empty world.
+fn round_end_payload(round_id: u64, winner: Option<&str>) -> String {
+    let winner = winner.map_or_else(|| "null".to_string(), json_string);
+    format!("{{\"round_id\":{},\"winner\":{}}}", round_id, winner)
+}
+
+/// Open a new round, releasing everyone from the hardcore graveyard.
+fn begin_round(ctx: &ReducerContext) {
+    let dead: Vec<Identity> = ctx.db.graveyard().iter().map(|g| g.identity).collect();
+    for identity in dead {
+        ctx.db.graveyard().identity().delete(identity);
+    }
+    let ends_at = ctx.timestamp + TimeDuration::from_micros(ROUND_DURATION_MICROS);
+    let round = ctx.db.game_round().insert(GameRound {
+        round_id: 0,
+        started_at: ctx.timestamp,
+        ends_at,
+        winner_identity: None,
+        winner_name: None,
+    });
+    ctx.db.round_end_schedule().insert(RoundEndSchedule {
+        scheduled_id: 0,
+        scheduled_at: ends_at.into(),
+        round_id: round.round_id,
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Safe zone (battle royale)
+// ---------------------------------------------------------------------------
+
+/// Start battle-royale mode: a safe zone covering the world that shrinks by
+/// `shrink_rate` units per second. Admin only.
+#[spacetimedb::reducer]
+pub fn start_safe_zone(ctx: &ReducerContext, shrink_rate: f32) {
+    if !is_admin(ctx) { return; }
+    if !shrink_rate.is_finite() || shrink_rate < 0.0 { return; }
+
+    let cfg = config(ctx);
+    let zone = SafeZone {
+        id: 0,
+        center_x: cfg.width() / 2.0,
+        center_y: cfg.height() / 2.0,
+        radius: (cfg.width() * cfg.width() + cfg.height() * cfg.height()).sqrt() / 2.0,
+        shrink_rate,
+    };
+    if ctx.db.safe_zone().id().find(0).is_some() {
+        ctx.db.safe_zone().id().update(zone);
+    } else {
+        ctx.db.safe_zone().insert(zone);
+    }
+}
+
+/// End battle-royale mode. Admin only.
+#[spacetimedb::reducer]
+pub fn clear_safe_zone(ctx: &ReducerContext) {
+    if !is_admin(ctx) { return; }
+    ctx.db.safe_zone().id().delete(0);
+}
+
+#[spacetimedb::reducer]
+pub fn shrink_zone(ctx: &ReducerContext, _schedule: ZoneShrinkSchedule) {
+    if is_paused(ctx) { return; }
+    let Some(zone) = ctx.db.safe_zone().id().find(0) else { return; };
+    let radius = (zone.radius - zone.shrink_rate).max(0.0);
+    ctx.db.safe_zone().id().update(SafeZone { radius, ..zone });
+}
+
+/// Drain `ZONE_DAMAGE_RATE` of the mass of every cell whose center is outside
+/// the safe zone. Main cells drained below `ZONE_MIN_MASS` eliminate the player;
+/// split cells below it are simply lost.
+#[spacetimedb::reducer]
+pub fn zone_damage(ctx: &ReducerContext, _schedule: ZoneDamageSchedule) {
+    if is_paused(ctx) { return; }
+    let Some(zone) = ctx.db.safe_zone().id().find(0) else { return; };
+
+    let cfg = config(ctx);
+    let mut hit: Vec<Identity> = Vec::new();
+    let cells: Vec<PlayerCell> = ctx.db.player_cell().iter().filter(|c| !zone.contains(c.x, c.y)).collect();
+    for cell in cells {
+        hit.push(cell.player_identity);
+        if let Some(new_mass) = zone_drained(cell.mass) {
+            ctx.db.player_cell().cell_id().update(PlayerCell {
+                mass: new_mass,
+                radius: cfg.mass_to_radius(new_mass),
+                ..cell
+            });
+        } else {
+            remove_cell(ctx, &cell);
+        }
+    }
+
+    let players: Vec<Player> = ctx.db.player().iter().filter(|p| !zone.contains(p.x, p.y)).collect();
+    for player in players {
+        let identity = player.identity;
+        let Some(new_mass) = zone_drained(player.mass) else {
+            emit_event(
+                ctx,
+                GameEventKind::Death,
+                Some(identity),
+                death_payload(None, &player.name),
+            );
+            remove_player(ctx, identity);
+            continue;
+        };
+        ctx.db.player().identity().update(Player {
+            mass: new_mass,
+            radius: cfg.mass_to_radius(new_mass),
+            ..player
+        });
+        hit.push(identity);
+    }
+
+    hit.sort();
+    hit.dedup();
+    for identity in hit {
+        mass_changed(ctx, identity);
+    }
+}
+
+/// Mass left on a cell after one tick outside the zone, or `None` once it
+/// falls below `ZONE_MIN_MASS` and the cell is lost.
+fn zone_drained(mass: f32) -> Option<f32> {
+    let new_mass = mass * (1.0 - ZONE_DAMAGE_RATE);
+    (new_mass >= ZONE_MIN_MASS).then_some(new_mass)
+}
+
+// ---------------------------------------------------------------------------
+// Minimap
+// ---------------------------------------------------------------------------
+
+/// Sync `minimap_blip` with current player positions and masses.
+#[spacetimedb::reducer]
+pub fn update_minimap(ctx: &ReducerContext, _schedule: MinimapSchedule) {
+    if is_paused(ctx) { return; }
+    let cfg = config(ctx);
+    let split_mass = mass_by_owner(ctx.db.player_cell().iter());
+
+    let mut blips: HashMap<Identity, MinimapBlip> = HashMap::new();
+    for player in ctx.db.player().iter() {
+        let mass = player.mass + split_mass.get(&player.identity).copied().unwrap_or(0.0);
+        blips.insert(player.identity, minimap_blip(&cfg, &player, mass));
+    }
+
+    let existing: Vec<MinimapBlip> = ctx.db.minimap_blip().iter().collect();
+    for old in existing {
+        match blips.remove(&old.identity) {
+            None => {
+                ctx.db.minimap_blip().identity().delete(old.identity);
+            }
+            Some(blip) => {
+                let moved = (blip.grid_x, blip.grid_y, blip.mass_bucket)
+                    != (old.grid_x, old.grid_y, old.mass_bucket);
+                if moved {
+                    ctx.db.minimap_blip().identity().update(blip);
+                }
+            }
+        }
+    }
+    for blip in blips.into_values() {
+        ctx.db.minimap_blip().insert(blip);
+    }
+}
+
+/// `player`'s minimap entry: the `MINIMAP_CELL_SIZE` square they are in and a
+/// log2 bucket of `total_mass` relative to `initial_mass`.
+fn minimap_blip(cfg: &GameConfig, player: &Player, total_mass: f32) -> MinimapBlip {
+    MinimapBlip {
+        identity: player.identity,
+        grid_x: (player.x / MINIMAP_CELL_SIZE).max(0.0) as u16,
+        grid_y: (player.y / MINIMAP_CELL_SIZE).max(0.0) as u16,
+        mass_bucket: (total_mass / cfg.initial_mass).log2().max(0.0) as u8,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// View culling
+// ---------------------------------------------------------------------------
+
+/// Replace the caller's `view_entity` rows with the players, cells, food and
+/// ejected mass whose centers lie within their `view_radius`.
+#[spacetimedb::reducer]
+pub fn refresh_view(ctx: &ReducerContext) {
+    let viewer = ctx.sender();
+    clear_view(ctx, viewer);
+    let Some(player) = ctx.db.player().identity().find(viewer) else { return; };
+
+    let in_view = |x: f32, y: f32| sees(&player, x, y);
+    let entry = |kind, entity_id, owner, x, y, radius| ViewEntity {
+        id: 0,
+        viewer,
+        kind,
+        entity_id,
+        owner,
+        x,
+        y,
+        radius,
+    };
+
+    let nearby = entities_near(ctx, player.x, player.y, player.view_radius);
+    let mut visible: Vec<ViewEntity> = Vec::new();
+    for p in nearby_players(ctx, &nearby).filter(|p| in_view(p.x, p.y)) {
+        visible.push(entry(ViewEntityKind::Player, 0, Some(p.identity), p.x, p.y, p.radius));
+    }
+    for f in nearby_food(ctx, &nearby).filter(|f| in_view(f.x, f.y)) {
+        visible.push(entry(ViewEntityKind::Food, f.id, None, f.x, f.y, f.radius));
+    }
+    for c in ctx.db.player_cell().iter().filter(|c| in_view(c.x, c.y)) {
+        visible.push(entry(ViewEntityKind::Cell, c.cell_id, Some(c.player_identity), c.x, c.y, c.radius));
+    }
+    for em in ctx.db.ejected_mass().iter().filter(|em| in_view(em.x, em.y)) {
+        visible.push(entry(ViewEntityKind::EjectedMass, em.id, None, em.x, em.y, em.radius));
+    }
+    for row in visible {
+        ctx.db.view_entity().insert(row);
+    }
+}
+
+/// Whether `viewer` sees a center at `(x, y)`: it lies within their `view_radius`.
+fn sees(viewer: &Player, x: f32, y: f32) -> bool {
+    within_reach(viewer.x, viewer.y, x, y, viewer.view_radius)
+}
+
+fn clear_view(ctx: &ReducerContext, viewer: Identity) {
+    let ids: Vec<u64> = ctx.db.view_entity().viewer().filter(viewer).map(|v| v.id).collect();
+    for id in ids {
+        ctx.db.view_entity().id().delete(id);
+    }
+}
+
+/// Replace the caller's `threat` rows with every cell, main or split, within
+/// `THREAT_RADIUS` of one of the caller's cells that it could eat: the
+/// caller's main cell takes their whole mass with it, so it needs
+/// `EAT_MASS_RATIO` over the total, while a split cell only needs to beat
+/// that cell. Teammates and disconnected players are never threats.
+#[spacetimedb::reducer]
+pub fn refresh_threats(ctx: &ReducerContext) {
+    let viewer = ctx.sender();
+    clear_threats(ctx, viewer);
+    let Some(player) = ctx.db.player().identity().find(viewer) else { return; };
+
+    let mut prey = vec![(player.x, player.y, total_mass(ctx, viewer))];
+    prey.extend(ctx.db.player_cell().player_identity().filter(viewer).map(|c| (c.x, c.y, c.mass)));
+
+    // Every other cell near any of the viewer's, once each
+    let mut hunters: Vec<(Identity, u64, (f32, f32, f32))> = Vec::new();
+    for &(x, y, _) in &prey {
+        for entity in entities_near(ctx, x, y, THREAT_RADIUS) {
+            let hunter = match entity {
+                GridEntity::Player(identity) => ctx.db.player().identity().find(identity)
+                    .map(|p| (identity, 0, (p.x, p.y, p.mass))),
+                GridEntity::Cell(cell_id) => ctx.db.player_cell().cell_id().find(cell_id)
+                    .map(|c| (c.player_identity, cell_id, (c.x, c.y, c.mass))),
+                _ => None,
+            };
+            let Some(hunter) = hunter else { continue; };
+            if hunter.0 == viewer || hunters.iter().any(|h| (h.0, h.1) == (hunter.0, hunter.1)) { continue; }
+            hunters.push(hunter);
+        }
+    }
+
+    for (threat_identity, threat_cell_id, body) in hunters {
+        let Some(owner) = ctx.db.player().identity().find(threat_identity) else { continue; };
+        if same_team(&player, &owner) || owner.disconnected_at.is_some() { continue; }
+        let Some(distance) = nearest_edible(body, &prey) else { continue; };
+        ctx.db.threat().insert(Threat { id: 0, viewer, threat_identity, threat_cell_id, mass: body.2, distance });
+    }
+}
+
+/// Distance from `hunter` to the closest of `prey` within `THREAT_RADIUS` that
+/// it is heavy enough to absorb. Both are `(x, y, mass)`.
+fn nearest_edible(hunter: (f32, f32, f32), prey: &[(f32, f32, f32)]) -> Option<f32> {
+    prey.iter()
+        .filter(|p| can_absorb(hunter.2, p.2))
+        .map(|p| (p.0 - hunter.0).hypot(p.1 - hunter.1))
+        .filter(|&d| d <= THREAT_RADIUS)
+        .min_by(f32::total_cmp)
+}
+
+fn clear_threats(ctx: &ReducerContext, viewer: Identity) {
+    let ids: Vec<u64> = ctx.db.threat().viewer().filter(viewer).map(|t| t.id).collect();
+    for id in ids {
+        ctx.db.threat().id().delete(id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Kill feed
+// ---------------------------------------------------------------------------
+
+/// Delete kill feed entries older than `KILL_EVENT_LIFETIME_MICROS`.
+#[spacetimedb::reducer]
+pub fn trim_kill_events(ctx: &ReducerContext, _schedule: KillEventCleanupSchedule) {
+    let cutoff = ctx.timestamp - TimeDuration::from_micros(KILL_EVENT_LIFETIME_MICROS);
+    let expired: Vec<u64> = ctx.db.kill_event().iter()
+        .filter(|e| e.timestamp <= cutoff)
+        .map(|e| e.id)
+        .collect();
+    for id in expired {
+        ctx.db.kill_event().id().delete(id);
+    }
+}
+
+fn record_kill(ctx: &ReducerContext, killer_identity: Identity, victim: &Player) {
+    let killer_name = ctx.db.player().identity().find(killer_identity)
+        .map(|p| p.name)
+        .unwrap_or_default();
+    emit_event(
+        ctx,
+        GameEventKind::Death,
+        Some(victim.identity),
+        death_payload(Some(&killer_name), &victim.name),
+    );
+    ctx.db.kill_event().insert(KillEvent {
+        id: 0,
+        killer_identity,
+        killer_name,
+        victim_name: victim.name.clone(),
+        timestamp: ctx.timestamp,
+    });
+}
+
+/// JSON payload of a `Death` event; `killer` is `None` for deaths to the zone.
+fn death_payload(killer: Option<&str>, victim: &str) -> String {
+    let killer = killer.map_or_else(|| "null".to_string(), json_string);
+    format!("{{\"killer\":{},\"victim\":{}}}", killer, json_string(victim))
+}
+
+// ---------------------------------------------------------------------------
+// Game events
+// ---------------------------------------------------------------------------
+
+/// Drop the oldest game events beyond `MAX_GAME_EVENTS`.
+#[spacetimedb::reducer]
+pub fn trim_game_events(ctx: &ReducerContext, _schedule: GameEventCleanupSchedule) {
+    let ids: Vec<u64> = ctx.db.game_event().iter().map(|e| e.id).collect();
+    for id in oldest_beyond(ids, MAX_GAME_EVENTS) {
+        ctx.db.game_event().id().delete(id);
+    }
+}
+
+/// The smallest (oldest) of `ids` that don't fit in the newest `cap`.
+fn oldest_beyond(mut ids: Vec<u64>, cap: usize) -> Vec<u64> {
+    if ids.len() <= cap { return Vec::new(); }
+    ids.sort_unstable();
+    ids.truncate(ids.len() - cap);
+    ids
+}
+
+/// JSON payload of a `Spawn` event.
+fn spawn_payload(name: &str, color: u32) -> String {
+    format!("{{\"name\":{},\"color\":{}}}", json_string(name), color)
+}
+
+/// JSON payload of an `Eject` event.
+fn mass_payload(mass: f32) -> String {
+    format!("{{\"mass\":{}}}", mass)
+}
+
+/// `s` as a quoted JSON string literal, for event payloads. Only `"`, `\` and
+/// control characters need escaping; everything else passes through as UTF-8.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn emit_event(ctx: &ReducerContext, kind: GameEventKind, identity: Option<Identity>, payload: String) {
+    ctx.db.game_event().insert(GameEvent {
+        id: 0,
+        kind,
+        identity,
+        payload,
+        timestamp: ctx.timestamp,
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Chat
+// ---------------------------------------------------------------------------
+
+/// Post a chat message. Control characters are stripped; empty messages and
+/// messages longer than `MAX_CHAT_LEN` characters are rejected.
+#[spacetimedb::reducer]
+pub fn send_chat(ctx: &ReducerContext, text: String) {
+    let identity = ctx.sender();
+    if is_banned(ctx, identity) { return; }
+
+    let Some(text) = clean_chat(&text) else { return; };
+
+    let sender_name = ctx.db.player().identity().find(identity)
+        .map(|p| p.name)
+        .unwrap_or_else(|| DEFAULT_NAME.to_string());
+    ctx.db.chat_message().insert(ChatMessage {
+        id: 0,
+        sender_identity: identity,
+        sender_name,
+        text,
+        timestamp: ctx.timestamp,
+    });
+}
+
+/// Strip control characters and surrounding whitespace from a chat message.
+/// Empty messages and ones over `MAX_CHAT_LEN` characters are dropped.
+fn clean_chat(raw: &str) -> Option<String> {
+    let cleaned: String = raw.chars().filter(|c| !c.is_control()).collect();
+    let text = cleaned.trim();
+    if text.is_empty() || text.chars().count() > MAX_CHAT_LEN {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+/// Delete chat messages older than `CHAT_MESSAGE_LIFETIME_MICROS`.
+#[spacetimedb::reducer]
+pub fn trim_chat_messages(ctx: &ReducerContext, _schedule: ChatCleanupSchedule) {
+    let cutoff = ctx.timestamp - TimeDuration::from_micros(CHAT_MESSAGE_LIFETIME_MICROS);
+    let expired: Vec<u64> = ctx.db.chat_message().iter()
+        .filter(|m| m.timestamp <= cutoff)
+        .map(|m| m.id)
+        .collect();
+    for id in expired {
+        ctx.db.chat_message().id().delete(id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Insert a new split half launched from `(origin_x, origin_y)` along `(nx, ny)`
+/// (see `update_split_motion`) in its owner's color, and schedule it to merge
+/// back once `GameConfig::merge_delay` has passed.
+fn insert_split_cell(
+    ctx: &ReducerContext,
+    identity: Identity,
+    origin_x: f32,
+    origin_y: f32,
+    mass: f32,
+    nx: f32,
+    ny: f32,
+) {
+    let cfg = config(ctx);
+    let color = ctx.db.player().identity().find(identity).map_or(0, |p| p.color);
+    let half = split_half(&cfg, (identity, color), (origin_x, origin_y), mass, (nx, ny), ctx.timestamp);
+    let cell = ctx.db.player_cell().insert(half);
+    grid_insert(ctx, GridEntity::Cell(cell.cell_id), cell.x, cell.y);
+    schedule_merge(ctx, identity);
+}
+
+/// A split half of `mass` for `(owner, color)`: it starts beside `origin` and is
+/// launched along `(nx, ny)` to coast out to ~2.5 radii under friction.
+fn split_half(
+    cfg: &GameConfig,
+    (owner, color): (Identity, u32),
+    (origin_x, origin_y): (f32, f32),
+    mass: f32,
+    (nx, ny): (f32, f32),
+    now: Timestamp,
+) -> PlayerCell {
+    let radius = cfg.mass_to_radius(mass);
+    let split_offset = radius * 2.5;
+    let (x, y) = cfg.clamp_to_world(
+        origin_x + nx * radius,
+        origin_y + ny * radius,
+        radius,
+    );
+    let launch_speed = (split_offset - radius) * EJECT_FRICTION;
+    PlayerCell {
+        cell_id: 0,
+        player_identity: owner,
+        x,
+        y,
+        radius,
+        mass,
+        last_move_micros: now.to_micros_since_unix_epoch(),
+        merge_at: now + cfg.merge_delay(mass),
+        vx: nx * launch_speed,
+        vy: ny * launch_speed,
+        color,
+    }
+}
+
+/// Replace the player's pending merge with one due at their earliest `merge_at`,
+/// so repeated splits never stack schedules.
+fn schedule_merge(ctx: &ReducerContext, identity: Identity) {
+    delete_merge_schedules(ctx, identity);
+    if let Some(merge_at) = next_merge_at(ctx.db.player_cell().player_identity().filter(identity)) {
+        ctx.db.split_merge_schedule().insert(SplitMergeSchedule {
+            scheduled_id: 0,
+            scheduled_at: merge_at.into(),
+            player_identity: identity,
+        });
+    }
+}
+
+/// When a player with split `cells` next needs a merge pass: the earliest
+/// merge timer among them, or never once they have none left.
+fn next_merge_at(cells: impl IntoIterator<Item = PlayerCell>) -> Option<Timestamp> {
+    cells.into_iter().map(|c| c.merge_at).min()
+}
+
+/// Place the `FOOD_CLUSTER_COUNT` hotspots, then fill the world to `max_food`.
+fn seed_food(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+    let mut rng = game_rng(ctx, &cfg);
+    for _ in 0..FOOD_CLUSTER_COUNT {
+        let (x, y) = cfg.random_position(&mut rng, 100.0);
+        ctx.db.food_cluster().insert(FoodCluster { id: 0, x, y });
+    }
+    spawn_food(ctx, cfg.max_food);
+}
+
+/// Spawn `n` pellets at fresh positions; used for seeding and every refill.
+fn spawn_food(ctx: &ReducerContext, n: u32) {
+    for _ in 0..n {
+        spawn_food_pellet(ctx);
+    }
+}
+
+/// Spawn one pellet placed per `GameConfig.food_distribution`; a few are golden.
+fn spawn_food_pellet(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+    let mut rng = game_rng(ctx, &cfg);
+    let clusters: Vec<FoodCluster> = match cfg.food_distribution {
+        FoodDistribution::Uniform => Vec::new(),
+        FoodDistribution::Clustered => ctx.db.food_cluster().iter().collect(),
+    };
+    let (x, y, radius, mass) = roll_food(&cfg, &clusters, &mut rng);
+    spawn_food_at(ctx, &cfg, x, y, radius, mass);
+}
+
+/// Position, radius and mass of a new pellet drawn from `rng`; `clusters` are
+/// only consulted for `FoodDistribution::Clustered`.
+fn roll_food(cfg: &GameConfig, clusters: &[FoodCluster], rng: &mut impl Rng) -> (f32, f32, f32, f32) {
+    let (x, y) = match cfg.food_distribution {
+        FoodDistribution::Uniform => cfg.random_position(rng, 20.0),
+        FoodDistribution::Clustered => clustered_position(cfg, clusters, rng),
+    };
+    let (radius, mass) = if rng.gen_bool(GOLDEN_FOOD_CHANCE) {
+        (GOLDEN_FOOD_RADIUS, GOLDEN_FOOD_MASS)
+    } else {
+        (cfg.food_radius, FOOD_MASS)
+    };
+    (x, y, radius, mass)
+}
+
+/// Insert and index a pellet at `(x, y)`, pushed clear of walls and kept inside
+/// the world. Every pellet enters the world through here.
+fn spawn_food_at(ctx: &ReducerContext, cfg: &GameConfig, x: f32, y: f32, radius: f32, mass: f32) {
+    let (x, y) = push_out_of_obstacles(&all_obstacles(ctx), x, y, radius);
+    let (x, y) = cfg.clamp_to_world(x, y, 20.0);
+    let food = ctx.db.food_pellet().insert(FoodPellet { id: 0, x, y, radius, mass });
+    grid_insert(ctx, GridEntity::Food(food.id), x, y);
+}
+
+/// A point scattered around a random hotspot with Gaussian (Box-Muller) offsets.
+/// Falls back to uniform placement if there are no hotspots.
+fn clustered_position(cfg: &GameConfig, clusters: &[FoodCluster], rng: &mut impl Rng) -> (f32, f32) {
+    if clusters.is_empty() {
+        return cfg.random_position(rng, 20.0);
+    }
+
+    let center = &clusters[rng.gen_range(0..clusters.len())];
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen_range(0.0..1.0);
+    let r = (-2.0 * u1.ln()).sqrt() * FOOD_CLUSTER_SPREAD;
+    let angle = u2 * std::f32::consts::TAU;
+    cfg.clamp_to_world(center.x + r * angle.cos(), center.y + r * angle.sin(), 20.0)
+}
+
+/// Drop all of `mass` as golden pellets within `spread` of `(x, y)`; see
+/// `scatter_split` for how it is divided.
+fn scatter_food(ctx: &ReducerContext, x: f32, y: f32, spread: f32, mass: f32) {
+    let cfg = config(ctx);
+    let mut rng = ctx.rng();
+    let (count, pellet_mass) = scatter_split(mass);
+    for _ in 0..count {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let dist = rng.gen_range(0.0..=spread);
+        let (px, py) = (x + dist * angle.cos(), y + dist * angle.sin());
+        spawn_food_at(ctx, &cfg, px, py, GOLDEN_FOOD_RADIUS, pellet_mass);
+    }
+}
+
+/// Pellet count and per-pellet mass for scattering `mass`: one pellet per
+/// `GOLDEN_FOOD_MASS` rounded up, at most `KILL_SCATTER_MAX`, sharing the mass
+/// equally so none is lost. Pellets keep the golden radius however heavy they
+/// get, so contact checks can rely on `max_food_radius`.
+fn scatter_split(mass: f32) -> (u32, f32) {
+    if mass.is_nan() || mass <= 0.0 {
+        return (0, 0.0);
+    }
+    let count = ((mass / GOLDEN_FOOD_MASS).ceil() as u32).clamp(1, KILL_SCATTER_MAX);
+    (count, mass / count as f32)
+}
+
+fn remove_food(ctx: &ReducerContext, food: &FoodPellet) {
+    ctx.db.food_pellet().id().delete(food.id);
+    grid_remove(ctx, GridEntity::Food(food.id), food.x, food.y);
+}
+
+fn remove_cell(ctx: &ReducerContext, cell: &PlayerCell) {
+    ctx.db.player_cell().cell_id().delete(cell.cell_id);
+    grid_remove(ctx, GridEntity::Cell(cell.cell_id), cell.x, cell.y);
+}
+
+fn remove_ejected_mass(ctx: &ReducerContext, em: &EjectedMass) {
+    ctx.db.ejected_mass().id().delete(em.id);
+    grid_remove(ctx, GridEntity::EjectedMass(em.id), em.x, em.y);
+}
+
+fn remove_virus(ctx: &ReducerContext, virus: &Virus) {
+    ctx.db.virus().id().delete(virus.id);
+    grid_remove(ctx, GridEntity::Virus(virus.id), virus.x, virus.y);
+}
+
+fn spawn_virus(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+    let mut rng = game_rng(ctx, &cfg);
+    let radius = cfg.mass_to_radius(VIRUS_MASS);
+    let (x, y) = cfg.random_position(&mut rng, 100.0);
+    let (x, y) = push_out_of_obstacles(&all_obstacles(ctx), x, y, radius);
+    let (x, y) = cfg.clamp_to_world(x, y, 100.0);
+    let virus = ctx.db.virus().insert(Virus {
+        id: 0,
+        x,
+        y,
+        radius,
+        mass: VIRUS_MASS,
+        feed_count: 0,
+    });
+    grid_insert(ctx, GridEntity::Virus(virus.id), x, y);
+}
+
+fn spawn_powerup(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+    let mut rng = game_rng(ctx, &cfg);
+    let (x, y) = cfg.random_position(&mut rng, 50.0);
+    let (x, y) = push_out_of_obstacles(&all_obstacles(ctx), x, y, POWERUP_RADIUS);
+    let (x, y) = cfg.clamp_to_world(x, y, 50.0);
+    let kind = if rng.gen_bool(0.5) { PowerUpKind::SpeedBoost } else { PowerUpKind::Shield };
+    ctx.db.power_up().insert(PowerUp { id: 0, x, y, kind });
+}
+
+/// Among `SPAWN_CANDIDATES` random positions, the one farthest from the edge of
+/// any cell big enough to eat a fresh player. Plain random if no such cell exists.
+fn safe_spawn_position(ctx: &ReducerContext, cfg: &GameConfig, rng: &mut impl Rng) -> (f32, f32) {
+    let mut threats: Vec<(f32, f32, f32)> = ctx.db.player().iter()
+        .filter(|p| can_absorb(p.mass, cfg.initial_mass))
+        .map(|p| (p.x, p.y, p.radius))
+        .collect();
+    threats.extend(ctx.db.player_cell().iter()
+        .filter(|c| can_absorb(c.mass, cfg.initial_mass))
+        .map(|c| (c.x, c.y, c.radius)));
+    safest_position(cfg, &threats, rng)
+}
+
+/// The best of `SPAWN_CANDIDATES` random positions: the one whose distance to the
+/// nearest threat's edge is largest. Any position will do when there are no threats.
+fn safest_position(cfg: &GameConfig, threats: &[(f32, f32, f32)], rng: &mut impl Rng) -> (f32, f32) {
+    if threats.is_empty() {
+        return cfg.random_position(rng, 100.0);
+    }
+
+    let clearance = |(x, y): (f32, f32)| {
+        threats.iter()
+            .map(|&(tx, ty, r)| ((tx - x) * (tx - x) + (ty - y) * (ty - y)).sqrt() - r)
+            .fold(f32::MAX, f32::min)
+    };
+    (0..SPAWN_CANDIDATES)
+        .map(|_| cfg.random_position(rng, 100.0))
+        .max_by(|a, b| clearance(*a).total_cmp(&clearance(*b)))
+        .unwrap_or_else(|| cfg.random_position(rng, 100.0))
+}
+
+fn default_config() -> GameConfig {
+    GameConfig {
+        id: 0,
+        max_food: MAX_FOOD,
+        world_width: WORLD_WIDTH as u32,
+        world_height: WORLD_HEIGHT as u32,
+        max_mass: MAX_MASS,
+        bot_count: BOT_COUNT,
+        max_players: MAX_PLAYERS,
+        world_shape: WorldShape::Rectangle,
+        decay_mass_exponent: DECAY_MASS_EXPONENT,
+        decay_interval_micros: DECAY_INTERVAL_MICROS,
+        decay_rate: MASS_DECAY_RATE,
+        eject_distance: EJECT_DISTANCE,
+        eject_spread: EJECT_SPREAD,
+        auto_resize: false,
+        idle_timeout_micros: IDLE_TIMEOUT_MICROS,
+        hardcore: false,
+        food_magnet: false,
+        paused: false,
+        food_distribution: FoodDistribution::Uniform,
+        food_replacement: FoodReplacement::TopUp,
+        initial_mass: INITIAL_MASS,
+        min_split_mass: MIN_SPLIT_MASS,
+        min_cell_mass: MIN_CELL_MASS,
+        merge_delay_micros: MERGE_DELAY_MICROS,
+        merge_delay_per_mass_micros: 0.0,
+        eject_mass_amount: EJECT_MASS_AMOUNT,
+        radius_scale: RADIUS_SCALE,
+        radius_exponent: RADIUS_EXPONENT,
+        food_radius: FOOD_RADIUS,
+        absorb_ratio: 1.0,
+        scatter_lost_mass: false,
+        eat_overlap_mode: EatOverlapMode::EdgeTouch,
+        eat_cover_factor: EAT_COVER_FACTOR,
+        rng_seed: None,
+    }
+}
+
+/// Generator for layout randomness (food, hotspots, spawns). With `rng_seed`
+/// set, each call returns the next generator in a sequence fixed by the seed;
+/// otherwise it is drawn from the transaction's RNG.
+fn game_rng(ctx: &ReducerContext, cfg: &GameConfig) -> StdRng {
+    let Some(seed) = cfg.rng_seed else {
+        return StdRng::seed_from_u64(ctx.rng().gen());
+    };
+    let draws = match ctx.db.rng_state().id().find(0) {
+        Some(state) => {
+            ctx.db.rng_state().id().update(RngState { draws: state.draws + 1, ..state });
+            state.draws
+        }
+        None => {
+            ctx.db.rng_state().insert(RngState { id: 0, draws: 1 });
+            0
+        }
+    };
+    seeded_rng(seed, draws)
+}
+
+/// The `draws`-th generator in the sequence fixed by `seed`.
+fn seeded_rng(seed: u64, draws: u64) -> StdRng {
+    StdRng::seed_from_u64(seed ^ draws.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// The singleton config row, falling back to the compiled-in defaults.
+fn config(ctx: &ReducerContext) -> GameConfig {
+    ctx.db.game_config().id().find(0).unwrap_or_else(default_config)
+}
+
+fn is_paused(ctx: &ReducerContext) -> bool {
+    config(ctx).paused
+}
+
+/// For a one-shot schedule firing at `now`: while paused, `Some` time to
+/// re-queue it for instead of running; `None` means run it now.
+fn paused_retry(cfg: &GameConfig, now: Timestamp) -> Option<Timestamp> {
+    cfg.paused.then(|| now + TimeDuration::from_micros(PAUSED_RETRY_MICROS))
+}
+
+/// The admins seeded on init: the publishing identity always, plus each valid,
+/// distinct identity among the configured `extra` hex strings.
+fn admin_identities(publisher: Identity, extra: &[&str]) -> Vec<Identity> {
+    let mut admins = vec![publisher];
+    for identity in extra.iter().filter_map(|hex| Identity::from_hex(hex).ok()) {
+        if !admins.contains(&identity) {
+            admins.push(identity);
+        }
+    }
+    admins
+}
+
+fn is_admin(ctx: &ReducerContext) -> bool {
+    ctx.db.admin().identity().find(ctx.sender()).is_some()
+}
+
+fn is_banned(ctx: &ReducerContext, identity: Identity) -> bool {
+    ctx.db.ban_list().identity().find(identity).is_some()
+}
+
+/// Strip control characters and surrounding whitespace. Empty names become
+/// `DEFAULT_NAME`; names longer than `MAX_NAME_LEN` characters are rejected.
+fn sanitize_name(raw: &str) -> Option<String> {
+    let cleaned: String = raw.chars().filter(|c| !c.is_control()).collect();
+    let trimmed = cleaned.trim();
+    if trimmed.is_empty() {
+        return Some(DEFAULT_NAME.to_string());
+    }
+    if trimmed.chars().count() > MAX_NAME_LEN {
+        return None;
+    }
+    Some(trimmed.to_string())
+}
+
+/// Append " 2", " 3", ... if another player already uses `name`.
+fn unique_name(ctx: &ReducerContext, identity: Identity, name: String) -> String {
+    let taken = |candidate: &str| {
+        ctx.db.player().iter().any(|p| p.identity != identity && p.name == candidate)
+    };
+    if !taken(&name) {
+        return name;
+    }
+    let mut n = 2;
+    loop {
+        let suffix = format!(" {n}");
+        let base: String = name.chars().take(MAX_NAME_LEN - suffix.len()).collect();
+        let candidate = format!("{}{suffix}", base.trim_end());
+        if !taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+fn can_absorb(eater_mass: f32, prey_mass: f32) -> bool {
+    eater_mass >= prey_mass * EAT_MASS_RATIO
+}
+
+/// Whether `identity`'s cell at `(x, y, radius)` touches `em` and may eat it.
+/// Main and split cells each reach from their own position.
+fn can_eat_eject(identity: Identity, (x, y, radius): (f32, f32, f32), em: &EjectedMass, now: Timestamp) -> bool {
+    !is_fresh_own_eject(em, identity, now) && within_reach(x, y, em.x, em.y, radius + em.radius)
+}
+
+/// Whether `em` was ejected by `identity` too recently for them to eat it back.
+fn is_fresh_own_eject(em: &EjectedMass, identity: Identity, now: Timestamp) -> bool {
+    em.ejector == identity && em.ejected_at + TimeDuration::from_micros(SELF_EAT_DELAY_MICROS) > now
+}
+
+fn same_team(a: &Player, b: &Player) -> bool {
+    a.team_id.is_some() && a.team_id == b.team_id
+}
+
+fn is_spawn_protected(player: &Player, now: Timestamp) -> bool {
+    player.spawn_protected_until > now
+}
+
+/// Spawn-protected, shielded, and disconnected (grace-period) players can't be eaten.
+fn is_uneatable(player: &Player, now: Timestamp) -> bool {
+    is_spawn_protected(player, now) || is_shielded(player, now) || player.disconnected_at.is_some()
+}
+
+fn is_shielded(player: &Player, now: Timestamp) -> bool {
+    player.shield_until > now
+}
+
+fn is_boosted(player: &Player, now: Timestamp) -> bool {
+    player.boost_until > now
+}
+
+/// Score for eating `food`: one point per unit of pellet mass.
+fn food_points(food: &FoodPellet) -> u64 {
+    food.mass as u64
+}
+
+/// Score for eating a player whose own score was `victim_score`.
+fn kill_points(victim_score: u64) -> u64 {
+    victim_score + KILL_SCORE
+}
+
+fn add_score(ctx: &ReducerContext, identity: Identity, points: u64) {
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    ctx.db.player().identity().update(Player {
+        score: player.score + points,
+        ..player
+    });
+}
+
+fn mark_active(ctx: &ReducerContext, identity: Identity) {
+    if let Some(player) = ctx.db.player().identity().find(identity) {
+        ctx.db.player().identity().update(Player { last_active_at: ctx.timestamp, ..player });
+    }
+}
+
+fn end_spawn_protection(ctx: &ReducerContext, identity: Identity) {
+    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    if !player.invulnerable && !is_spawn_protected(&player, ctx.timestamp) { return; }
+    ctx.db.player().identity().update(unprotected(player, ctx.timestamp));
+}
+
+/// `player` with spawn protection, and its `invulnerable` flag, ended at `now`.
+fn unprotected(player: Player, now: Timestamp) -> Player {
+    Player { spawn_protected_until: now, invulnerable: false, ..player }
+}
+
+/// Add the player's current connected stretch to their lifetime playtime.
+fn credit_playtime(ctx: &ReducerContext, player: &Player) {
+    let played_micros = online_micros(player, ctx.timestamp);
+    with_stats(ctx, player.identity, |stats| stats.total_playtime_micros += played_micros);
+}
+
+/// Time `player` has been connected since `online_since`; 0 while disconnected,
+/// since that stretch was already credited when they dropped.
+fn online_micros(player: &Player, now: Timestamp) -> u64 {
+    if player.disconnected_at.is_some() {
+        return 0;
+    }
+    let micros = now.to_micros_since_unix_epoch() - player.online_since.to_micros_since_unix_epoch();
+    micros.max(0) as u64
+}
+
+/// Delete a player's main row, all of their split cells, and their pending merges.
+fn remove_player(ctx: &ReducerContext, identity: Identity) {
+    if let Some(player) = ctx.db.player().identity().find(identity) {
+        grid_remove(ctx, GridEntity::Player(identity), player.x, player.y);
+        credit_playtime(ctx, &player);
+    }
+    let team_id = ctx.db.player().identity().find(identity).and_then(|p| p.team_id);
+    ctx.db.player().identity().delete(identity);
+    delete_player_cells(ctx, identity);
+    delete_merge_schedules(ctx, identity);
+    clear_view(ctx, identity);
+    clear_threats(ctx, identity);
+    update_leaderboard(ctx, identity, 0.0);
+    if let Some(team_id) = team_id {
+        refresh_team_score(ctx, team_id);
+    }
+}
+
+/// Total split-cell mass per owner.
+fn mass_by_owner(cells: impl Iterator<Item = PlayerCell>) -> HashMap<Identity, f32> {
+    let mut split_mass: HashMap<Identity, f32> = HashMap::new();
+    for cell in cells {
+        *split_mass.entry(cell.player_identity).or_default() += cell.mass;
+    }
+    split_mass
+}
+
+/// Combined mass of a player's main cell and split cells (0 if not spawned).
+fn total_mass(ctx: &ReducerContext, identity: Identity) -> f32 {
+    let Some(player) = ctx.db.player().identity().find(identity) else { return 0.0; };
+    let split_mass: f32 = ctx.db.player_cell().player_identity().filter(identity)
+        .map(|c| c.mass)
+        .sum();
+    player.mass + split_mass
+}
+
+/// Grow `player`'s main cell to `mass` (capped at `max_mass`). The center is
+/// pushed out of any wall and re-clamped for the new radius so a cell near the
+/// edge never overhangs it.
+fn grow_player(ctx: &ReducerContext, player: Player, mass: f32) {
+    let (x, y, mass, radius) = grown(&config(ctx), &all_obstacles(ctx), (player.x, player.y), mass);
+    grid_move(ctx, GridEntity::Player(player.identity), (player.x, player.y), (x, y));
+    ctx.db.player().identity().update(Player { x, y, mass, radius, ..player });
+}
+
+/// `grow_player` for a split cell.
+fn grow_cell(ctx: &ReducerContext, cell: PlayerCell, mass: f32) {
+    let (x, y, mass, radius) = grown(&config(ctx), &all_obstacles(ctx), (cell.x, cell.y), mass);
+    grid_move(ctx, GridEntity::Cell(cell.cell_id), (cell.x, cell.y), (x, y));
+    ctx.db.player_cell().cell_id().update(PlayerCell { x, y, mass, radius, ..cell });
+}
+
+/// Position, mass and radius of a cell at `(x, y)` grown to `mass`: capped at
+/// `max_mass`, then moved so the larger radius clears walls and stays inside the world.
+fn grown(cfg: &GameConfig, obstacles: &[Obstacle], (x, y): (f32, f32), mass: f32) -> (f32, f32, f32, f32) {
+    let mass = mass.min(cfg.max_mass);
+    let radius = cfg.mass_to_radius(mass);
+    let (x, y) = push_out_of_obstacles(obstacles, x, y, radius);
+    let (x, y) = cfg.clamp_to_world(x, y, radius);
+    (x, y, mass, radius)
+}
+
+/// Call after any change to a player's mass to keep derived state in sync.
+fn mass_changed(ctx: &ReducerContext, identity: Identity) {
+    let mass = total_mass(ctx, identity);
+    update_leaderboard(ctx, identity, mass);
+    if let Some(player) = ctx.db.player().identity().find(identity) {
+        let team_id = player.team_id;
+        let view = view_radius(&config(ctx), mass);
+        if player.view_radius != view {
+            ctx.db.player().identity().update(Player { view_radius: view, ..player });
+        }
+        if let Some(team_id) = team_id {
+            refresh_team_score(ctx, team_id);
+        }
+    }
+    with_stats(ctx, identity, |stats| {
+        stats.max_mass_reached = stats.max_mass_reached.max(mass);
+    });
+}
+
+/// Recompute every player's `view_radius` after a bulk mass change.
+fn refresh_view_radii(ctx: &ReducerContext) {
+    let cfg = config(ctx);
+    let split_mass = mass_by_owner(ctx.db.player_cell().iter());
+    let players: Vec<Player> = ctx.db.player().iter().collect();
+    for player in players {
+        let mass = player.mass + split_mass.get(&player.identity).copied().unwrap_or(0.0);
+        let view = view_radius(&cfg, mass);
+        if player.view_radius != view {
+            ctx.db.player().identity().update(Player { view_radius: view, ..player });
+        }
+    }
+}
+
+/// Recompute the leaderboard only if this player's mass change can affect it:
+/// they are already ranked, or they now beat the lowest ranked entry.
+fn update_leaderboard(ctx: &ReducerContext, identity: Identity, mass: f32) {
+    let entries: Vec<LeaderboardEntry> = ctx.db.leaderboard().iter().collect();
+    if leaderboard_affected(&entries, identity, mass) {
+        refresh_leaderboard(ctx);
+    }
+}
+
+/// Whether `identity` now at `mass` can change the board: they are on it, or
+/// they beat its lightest entry (any mass qualifies while it isn't full).
+fn leaderboard_affected(entries: &[LeaderboardEntry], identity: Identity, mass: f32) -> bool {
+    let on_board = entries.iter().any(|e| e.identity == identity);
+    let threshold = if entries.len() < LEADERBOARD_SIZE {
+        f32::MIN
+    } else {
+        entries.iter().map(|e| e.mass).fold(f32::MAX, f32::min)
+    };
+    on_board || mass > threshold
+}
+
+/// Rebuild the leaderboard from scratch. Equal masses are ordered by earliest
+/// spawn, then identity, so ties never flicker between recomputations.
+fn refresh_leaderboard(ctx: &ReducerContext) {
+    let split_mass = mass_by_owner(ctx.db.player_cell().iter());
+
+    let ranked = rank_players(ctx.db.player().iter(), &split_mass);
+
+    let ranks: Vec<u32> = ctx.db.leaderboard().iter().map(|e| e.rank).collect();
+    for rank in ranks {
+        ctx.db.leaderboard().rank().delete(rank);
+    }
+    for entry in ranked {
+        ctx.db.leaderboard().insert(entry);
+    }
+}
+
+/// The top `LEADERBOARD_SIZE` players by total mass, given each identity's
+/// mass in split cells.
+fn rank_players(players: impl IntoIterator<Item = Player>, split_mass: &HashMap<Identity, f32>) -> Vec<LeaderboardEntry> {
+    let mut ranked: Vec<(Identity, String, f32, Option<u32>, Timestamp)> = players.into_iter()
+        .map(|p| {
+            let mass = p.mass + split_mass.get(&p.identity).copied().unwrap_or(0.0);
+            (p.identity, p.name, mass, p.team_id, p.spawned_at)
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.2.total_cmp(&a.2)
+            .then_with(|| a.4.cmp(&b.4))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    ranked.truncate(LEADERBOARD_SIZE);
+    ranked.into_iter()
+        .enumerate()
+        .map(|(i, (identity, name, mass, team_id, _))| LeaderboardEntry {
+            rank: i as u32 + 1,
+            identity,
+            name,
+            mass,
+            team_id,
+        })
+        .collect()
+}
+
+/// Apply `f` to the identity's lifetime stats, creating the row on first use.
+fn with_stats(ctx: &ReducerContext, identity: Identity, f: impl FnOnce(&mut PlayerStats)) {
+    match ctx.db.player_stats().identity().find(identity) {
+        Some(mut stats) => {
+            f(&mut stats);
+            award_achievements(ctx, &stats);
+            ctx.db.player_stats().identity().update(stats);
+        }
+        None => {
+            let mut stats = PlayerStats {
+                identity,
+                food_eaten: 0,
+                players_eaten: 0,
+                times_split: 0,
+                mass_ejected: 0.0,
+                max_mass_reached: 0.0,
+                total_playtime_micros: 0,
+                deaths: 0,
+            };
+            f(&mut stats);
+            award_achievements(ctx, &stats);
+            ctx.db.player_stats().insert(stats);
+        }
+    }
+}
+
+/// Unlock every achievement whose threshold `stats` has crossed. Already
+/// unlocked ones are skipped, so calling this repeatedly is harmless.
+fn award_achievements(ctx: &ReducerContext, stats: &PlayerStats) {
+    for kind in stats.achievements() {
+        if ctx.db.achievement().identity().filter(stats.identity).any(|a| a.kind == kind) {
+            continue;
+        }
+        ctx.db.achievement().insert(Achievement {
+            id: 0,
+            identity: stats.identity,
+            kind,
+            unlocked_at: ctx.timestamp,
+        });
+    }
+}
+
+/// Recompute one team's aggregate score, dropping the row once the team is empty.
+fn refresh_team_score(ctx: &ReducerContext, team_id: u32) {
+    let members: Vec<Identity> = ctx.db.player().iter()
+        .filter(|p| p.team_id == Some(team_id))
+        .map(|p| p.identity)
+        .collect();
+    if members.is_empty() {
+        ctx.db.team_score().team_id().delete(team_id);
+        return;
+    }
+
+    let score = TeamScore {
+        team_id,
+        total_mass: members.iter().map(|id| total_mass(ctx, *id)).sum(),
+        member_count: members.len() as u32,
+    };
+    if ctx.db.team_score().team_id().find(team_id).is_some() {
+        ctx.db.team_score().team_id().update(score);
+    } else {
+        ctx.db.team_score().insert(score);
+    }
+}
+
+/// Returns false if the caller performed `action` less than `cooldown_micros` ago;
+/// otherwise records the call and returns true.
+fn check_rate(ctx: &ReducerContext, action: RateLimitedAction, cooldown_micros: i64) -> bool {
+    let identity = ctx.sender();
+    let now = ctx.timestamp.to_micros_since_unix_epoch();
+    let existing = ctx.db.rate_limit().identity().find(identity);
+    let is_new = existing.is_none();
+    let mut limit = existing.unwrap_or(RateLimit {
+        identity,
+        last_eject_micros: i64::MIN,
+        last_split_micros: i64::MIN,
+    });
+
+    if !limit.try_take(action, now, cooldown_micros) {
+        return false;
+    }
+
+    if is_new {
+        ctx.db.rate_limit().insert(limit);
+    } else {
+        ctx.db.rate_limit().identity().update(limit);
+    }
+    true
+}
+
+fn delete_merge_schedules(ctx: &ReducerContext, identity: Identity) {
+    let ids: Vec<u64> = ctx.db.split_merge_schedule().player_identity().filter(identity)
+        .map(|s| s.scheduled_id)
+        .collect();
+    for id in ids {
+        ctx.db.split_merge_schedule().scheduled_id().delete(id);
+    }
+}
+
+fn delete_player_cells(ctx: &ReducerContext, identity: Identity) {
+    let cells: Vec<PlayerCell> = ctx.db.player_cell().player_identity().filter(identity).collect();
+    for cell in cells {
+        remove_cell(ctx, &cell);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Spatial index
+// ---------------------------------------------------------------------------
+
+fn grid_insert(ctx: &ReducerContext, entity: GridEntity, x: f32, y: f32) {
+    ctx.db.grid_cell().insert(GridCell { id: 0, bucket: grid_bucket(x, y), entity });
+}
+
+fn grid_remove(ctx: &ReducerContext, entity: GridEntity, x: f32, y: f32) {
+    let ids: Vec<u64> = ctx.db.grid_cell().bucket().filter(grid_bucket(x, y))
+        .filter(|g| g.entity == entity)
+        .map(|g| g.id)
+        .collect();
+    for id in ids {
+        ctx.db.grid_cell().id().delete(id);
+    }
+}
+
+/// Call whenever a tracked entity moves; only rewrites the index when it
+/// crosses into a different bucket.
+fn grid_move(ctx: &ReducerContext, entity: GridEntity, from: (f32, f32), to: (f32, f32)) {
+    if grid_bucket(from.0, from.1) == grid_bucket(to.0, to.1) { return; }
+    grid_remove(ctx, entity, from.0, from.1);
+    grid_insert(ctx, entity, to.0, to.1);
+}
+
+/// Entities whose bucket overlaps the square around `(x, y)` of half-width
+/// `radius`. A superset of those actually in range; callers check distance.
+fn entities_near(ctx: &ReducerContext, x: f32, y: f32, radius: f32) -> Vec<GridEntity> {
+    let mut found = Vec::new();
+    for bucket in buckets_near(x, y, radius) {
+        found.extend(ctx.db.grid_cell().bucket().filter(bucket).map(|g| g.entity));
+    }
+    found
+}
+
+/// Keys of the buckets overlapping the square around `(x, y)` of half-width `radius`.
+fn buckets_near(x: f32, y: f32, radius: f32) -> impl Iterator<Item = u64> {
+    let (min_x, max_x) = (grid_coord(x - radius), grid_coord(x + radius));
+    let (min_y, max_y) = (grid_coord(y - radius), grid_coord(y + radius));
+    (min_x..=max_x).flat_map(move |grid_x| (min_y..=max_y).map(move |grid_y| bucket_key(grid_x, grid_y)))
+}
+
+fn nearby_players<'a>(ctx: &'a ReducerContext, nearby: &'a [GridEntity]) -> impl Iterator<Item = Player> + 'a {
+    nearby.iter().filter_map(|e| match e {
+        GridEntity::Player(identity) => ctx.db.player().identity().find(*identity),
+        _ => None,
+    })
+}
+
+fn nearby_food<'a>(ctx: &'a ReducerContext, nearby: &'a [GridEntity]) -> impl Iterator<Item = FoodPellet> + 'a {
+    nearby.iter().filter_map(|e| match e {
+        GridEntity::Food(id) => ctx.db.food_pellet().id().find(*id),
+        _ => None,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests (pure helpers only; reducers need a live database)
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn capped_move_limits_distance_to_speed_times_dt() {
+        let cfg = default_config();
+        let dt = 0.1;
+        let max_dist = cell_speed(&cfg, INITIAL_MASS) * dt;
+        let (x, y) = capped_move(0.0, 0.0, 3000.0, 4000.0, max_dist);
+        assert!(close((x * x + y * y).sqrt(), max_dist));
+        // Direction is preserved
+        assert!(close(x / y, 3.0 / 4.0));
+    }
+
+    #[test]
+    fn capped_move_leaves_legitimate_moves_alone() {
+        assert_eq!(capped_move(10.0, 10.0, 13.0, 14.0, 5.0), (13.0, 14.0));
+        assert_eq!(capped_move(10.0, 10.0, 10.0, 10.0, 0.0), (10.0, 10.0));
+    }
+
+    #[test]
+    fn second_rapid_update_is_clamped_to_its_elapsed_time() {
+        let cfg = default_config();
+        let t0 = 1_000_000;
+        let first_budget = move_budget(&cfg, INITIAL_MASS, t0, t0 + 100_000);
+        let (x1, y1) = capped_move(0.0, 0.0, 1000.0, 0.0, first_budget);
+        assert!(close(x1, first_budget));
+
+        // 5 ms later the client asks for another 1000 units
+        let second_budget = move_budget(&cfg, INITIAL_MASS, t0 + 100_000, t0 + 105_000);
+        let (x2, y2) = capped_move(x1, y1, x1 + 1000.0, 0.0, second_budget);
+        assert!(close(x2 - x1, second_budget));
+        assert!(close(y2, 0.0));
+        assert!(close(second_budget * 20.0, first_budget));
+    }
+
+    #[test]
+    fn move_budget_ignores_clock_skew_and_caps_long_gaps() {
+        let cfg = default_config();
+        assert_eq!(move_budget(&cfg, INITIAL_MASS, 2_000_000, 1_000_000), 0.0);
+        let capped = move_budget(&cfg, INITIAL_MASS, 0, 3_600_000_000);
+        let limit = cell_speed(&cfg, INITIAL_MASS) * MOVE_SPEED_TOLERANCE * MAX_MOVE_ELAPSED_SECS;
+        assert!(close(capped, limit));
+    }
+
+    #[test]
+    fn food_is_eaten_only_on_real_contact() {
+        let (player_r, food_r) = (20.0, 5.0);
+        // Touching edges count; a hair further out does not
+        assert!(within_reach(100.0, 100.0, 125.0, 100.0, player_r + food_r));
+        assert!(!within_reach(100.0, 100.0, 125.01, 100.0, player_r + food_r));
+        // Diagonal: (15, 20) is exactly 25 away
+        assert!(within_reach(0.0, 0.0, 15.0, 20.0, player_r + food_r));
+        assert!(!within_reach(0.0, 0.0, 15.0, 20.1, player_r + food_r));
+        // The old check doubled the contact distance and would have allowed this
+        assert!(!within_reach(0.0, 0.0, 30.0, 30.0, player_r + food_r));
+    }
+
+    #[test]
+    fn sanitize_name_strips_control_characters() {
+        assert_eq!(sanitize_name("  Bo\u{7}b\n ").as_deref(), Some("Bob"));
+        assert_eq!(sanitize_name("tab\there").as_deref(), Some("tabhere"));
+    }
+
+    #[test]
+    fn sanitize_name_defaults_empty_input() {
+        assert_eq!(sanitize_name("").as_deref(), Some(DEFAULT_NAME));
+        assert_eq!(sanitize_name(" \t\r\n ").as_deref(), Some(DEFAULT_NAME));
+    }
+
+    #[test]
+    fn sanitize_name_counts_chars_not_bytes() {
+        let multibyte = "é".repeat(MAX_NAME_LEN);
+        assert_eq!(sanitize_name(&multibyte), Some(multibyte.clone()));
+        assert_eq!(sanitize_name(&"é".repeat(MAX_NAME_LEN + 1)), None);
+        // Trimming happens before the length check
+        let padded = format!("   {}   ", "a".repeat(MAX_NAME_LEN));
+        assert_eq!(sanitize_name(&padded), Some("a".repeat(MAX_NAME_LEN)));
+    }
+
+    #[test]
+    fn cell_speed_is_normalised_and_non_increasing() {
+        let cfg = default_config();
+        assert!(close(cell_speed(&cfg, INITIAL_MASS), BASE_SPEED));
+        let masses = [1.0, 10.0, INITIAL_MASS, 100.0, 500.0, 2000.0, 10_000.0];
+        for pair in masses.windows(2) {
+            assert!(cell_speed(&cfg, pair[1]) <= cell_speed(&cfg, pair[0]));
+        }
+        // Tiny or zero mass is treated as 1, never infinite
+        assert!(cell_speed(&cfg, 0.0).is_finite());
+    }
+
+    #[test]
+    fn clamp_to_world_keeps_margin_in_rectangle() {
+        let cfg = default_config();
+        assert_eq!(cfg.clamp_to_world(-50.0, 5000.0, 10.0), (10.0, cfg.height() - 10.0));
+        assert_eq!(cfg.clamp_to_world(100.0, 200.0, 10.0), (100.0, 200.0));
+    }
+
+    #[test]
+    fn clamp_to_world_projects_onto_circle_rim() {
+        let cfg = GameConfig { world_shape: WorldShape::Circle, ..default_config() };
+        let (cx, cy) = (cfg.width() / 2.0, cfg.height() / 2.0);
+        let (x, y) = cfg.clamp_to_world(cx + 10_000.0, cy, 20.0);
+        assert!(close(x, cx + cx - 20.0));
+        assert!(close(y, cy));
+        // Points already inside the disc are left alone
+        assert_eq!(cfg.clamp_to_world(cx + 100.0, cy - 100.0, 20.0), (cx + 100.0, cy - 100.0));
+    }
+
+    #[test]
+    fn clamp_to_world_pulls_a_corner_position_onto_the_disc() {
+        let cfg = GameConfig { world_shape: WorldShape::Circle, ..default_config() };
+        let (cx, cy) = (cfg.width() / 2.0, cfg.height() / 2.0);
+        let rim = cx.min(cy) - 30.0;
+        // The rectangle's corner is inside the rectangle but outside the disc
+        let (x, y) = cfg.clamp_to_world(0.0, 0.0, 30.0);
+        let (dx, dy) = (x - cx, y - cy);
+        assert!(close((dx * dx + dy * dy).sqrt(), rim));
+        // Projected along the diagonal toward the center
+        assert!(close(dx / dy, cx / cy) && dx < 0.0);
+        // Clamping is idempotent
+        assert_eq!(cfg.clamp_to_world(x, y, 30.0), (x, y));
+    }
+
+    #[test]
+    fn random_position_stays_inside_margin() {
+        let cfg = GameConfig { world_shape: WorldShape::Circle, ..default_config() };
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let (x, y) = cfg.random_position(&mut rng, 100.0);
+            assert_eq!(cfg.clamp_to_world(x, y, 100.0), (x, y));
+        }
+    }
+
+    #[test]
+    fn decayed_mass_hits_heavier_cells_harder() {
+        let cfg = default_config();
+        let lost_fraction = |mass: f32| 1.0 - decayed_mass(&cfg, mass) / mass;
+        assert!(close(lost_fraction(INITIAL_MASS), 1.0 - cfg.decay_rate));
+        assert!(lost_fraction(1000.0) > lost_fraction(100.0));
+        assert!(lost_fraction(MAX_MASS * 100.0) <= MAX_DECAY_LOSS + 1e-6);
+
+        // An exponent of 0 makes the fraction flat
+        let flat = GameConfig { decay_mass_exponent: 0.0, ..default_config() };
+        let flat_fraction = |mass: f32| 1.0 - decayed_mass(&flat, mass) / mass;
+        assert!(close(flat_fraction(100.0), flat_fraction(5000.0)));
+    }
+
+    #[test]
+    fn grid_coord_buckets_by_cell_size() {
+        assert_eq!(grid_coord(0.0), 0);
+        assert_eq!(grid_coord(GRID_CELL_SIZE - 0.01), 0);
+        assert_eq!(grid_coord(GRID_CELL_SIZE), 1);
+        assert_eq!(grid_coord(WORLD_WIDTH), (WORLD_WIDTH / GRID_CELL_SIZE) as u32);
+    }
+
+    #[test]
+    fn grid_coord_clamps_negative_and_handles_out_of_range() {
+        assert_eq!(grid_coord(-0.01), 0);
+        assert_eq!(grid_coord(-1e9), 0);
+        assert_eq!(grid_coord(f32::NAN), 0);
+        assert_eq!(grid_coord(WORLD_WIDTH * 2.0), (WORLD_WIDTH * 2.0 / GRID_CELL_SIZE) as u32);
+    }
+
+    #[test]
+    fn grid_bucket_keys_are_distinct_per_axis() {
+        assert_ne!(grid_bucket(0.0, GRID_CELL_SIZE), grid_bucket(GRID_CELL_SIZE, 0.0));
+        assert_eq!(grid_bucket(10.0, 10.0), grid_bucket(GRID_CELL_SIZE - 1.0, GRID_CELL_SIZE - 1.0));
+        assert_eq!(bucket_key(3, 4), (3u64 << 32) | 4);
+    }
+
+    #[test]
+    fn can_absorb_boundary_is_the_mass_ratio() {
+        let prey = 100.0;
+        assert!(can_absorb(prey * EAT_MASS_RATIO, prey));
+        assert!(!can_absorb(prey * EAT_MASS_RATIO - 0.01, prey));
+        assert!(!can_absorb(prey, prey));
+    }
+
+    #[test]
+    fn decayed_mass_rate_of_one_means_no_decay() {
+        let cfg = GameConfig { decay_rate: 1.0, ..default_config() };
+        for mass in [INITIAL_MASS, 500.0, MAX_MASS] {
+            assert_eq!(decayed_mass(&cfg, mass), mass);
+        }
+    }
+
+    #[test]
+    fn view_radius_never_shrinks_below_base() {
+        let cfg = default_config();
+        assert!(close(view_radius(&cfg, INITIAL_MASS), BASE_VIEW_RADIUS));
+        assert!(close(view_radius(&cfg, 0.0), BASE_VIEW_RADIUS));
+        assert!(close(view_radius(&cfg, INITIAL_MASS * 16.0), BASE_VIEW_RADIUS * 2.0));
+        let masses = [0.0, INITIAL_MASS, 100.0, 1000.0, 10_000.0];
+        for pair in masses.windows(2) {
+            assert!(view_radius(&cfg, pair[1]) >= view_radius(&cfg, pair[0]));
+        }
+    }
+
+    #[test]
+    fn all_finite_rejects_nan_and_infinity() {
+        assert!(all_finite(&[0.0, -1.5, 3000.0]));
+        assert!(!all_finite(&[0.0, f32::NAN]));
+        assert!(!all_finite(&[f32::INFINITY]));
+        assert!(all_finite(&[]));
+    }
+
+    #[test]
+    fn mass_to_radius_follows_the_size_curve() {
+        let cfg = default_config();
+        assert!(close(cfg.mass_to_radius(100.0), RADIUS_SCALE * 10.0));
+        assert_eq!(cfg.mass_to_radius(-1.0), 0.0);
+        assert!(cfg.mass_to_radius(400.0) > cfg.mass_to_radius(100.0));
+        let linear = GameConfig { radius_scale: 1.0, radius_exponent: 1.0, ..default_config() };
+        assert!(close(linear.mass_to_radius(42.0), 42.0));
+    }
+
+    #[test]
+    fn configured_size_curve_drives_food_and_contact_reach() {
+        let cfg = GameConfig { radius_scale: 3.0, radius_exponent: 0.25, food_radius: 4.0, ..default_config() };
+        assert!(close(cfg.mass_to_radius(16.0), 6.0));
+        assert!(close(cfg.mass_to_radius(256.0), 12.0));
+        // Golden pellets are the largest food unless food is configured bigger
+        assert_eq!(cfg.max_food_radius(), GOLDEN_FOOD_RADIUS);
+        let big_food = GameConfig { food_radius: 25.0, ..cfg };
+        assert_eq!(big_food.max_food_radius(), 25.0);
+        // Small cells reach out to the largest pellet; big ones to a cell their own size
+        assert_eq!(contact_reach(&cfg, 4.0), 4.0 + GOLDEN_FOOD_RADIUS);
+        assert_eq!(contact_reach(&big_food, 4.0), 29.0);
+        assert_eq!(contact_reach(&cfg, 50.0), 100.0);
+    }
+
+    #[test]
+    fn names_with_combining_marks_make_valid_json() {
+        let name = sanitize_name("Zoe\u{301}").unwrap();
+        assert_eq!(name, "Zoe\u{301}");
+        assert_eq!(json_string(&name), "\"Zoe\u{301}\"");
+        assert_eq!(json_string("a\"b\\c\u{1}"), "\"a\\\"b\\\\c\\u0001\"");
+        assert_eq!(json_string("soft\u{ad}hyphen\u{200d}"), "\"soft\u{ad}hyphen\u{200d}\"");
+    }
+
+    #[test]
+    fn can_split_needs_both_thresholds() {
+        let cfg = default_config();
+        assert!(!cfg.can_split(cfg.min_split_mass - 1.0));
+        assert!(cfg.can_split(cfg.min_split_mass.max(cfg.min_cell_mass * 2.0)));
+        let strict = GameConfig { min_cell_mass: cfg.min_split_mass, ..default_config() };
+        assert!(!strict.can_split(strict.min_split_mass));
+    }
+
+    #[test]
+    fn kd_ratio_treats_no_deaths_as_one() {
+        let stats = |players_eaten, deaths| PlayerStats {
+            identity: Identity::ZERO,
+            food_eaten: 0,
+            players_eaten,
+            times_split: 0,
+            mass_ejected: 0.0,
+            max_mass_reached: 0.0,
+            total_playtime_micros: 0,
+            deaths,
+        };
+        assert_eq!(stats(3, 0).kd_ratio(), 3.0);
+        assert_eq!(stats(3, 2).kd_ratio(), 1.5);
+        assert_eq!(stats(0, 5).kd_ratio(), 0.0);
+    }
+
+    #[test]
+    fn merge_delay_scales_with_mass() {
+        let flat = default_config();
+        assert_eq!(flat.merge_delay(10.0), flat.merge_delay(1000.0));
+        assert_eq!(flat.merge_delay(10.0).to_micros(), flat.merge_delay_micros);
+
+        let scaled = GameConfig { merge_delay_per_mass_micros: 1000.0, ..default_config() };
+        assert_eq!(scaled.merge_delay(100.0).to_micros(), scaled.merge_delay_micros + 100_000);
+        assert!(scaled.merge_delay(200.0) > scaled.merge_delay(100.0));
+        // Negative mass never shortens the delay
+        assert_eq!(scaled.merge_delay(-5.0).to_micros(), scaled.merge_delay_micros);
+    }
+
+    fn wall() -> Obstacle {
+        Obstacle { id: 1, x: 100.0, y: 100.0, width: 200.0, height: 100.0 }
+    }
+
+    #[test]
+    fn obstacle_overlap_at_edges_and_corners() {
+        let w = wall();
+        // Touching an edge or corner exactly is not an overlap
+        assert!(!w.overlaps(90.0, 150.0, 10.0));
+        assert!(w.overlaps(91.0, 150.0, 10.0));
+        let d = 10.0 / 2f32.sqrt();
+        assert!(!w.overlaps(100.0 - d - 0.01, 100.0 - d - 0.01, 10.0));
+        assert!(w.overlaps(100.0 - d + 0.5, 100.0 - d + 0.5, 10.0));
+        // Inside the box's bounding square but clear of the rounded corner
+        assert!(!w.overlaps(93.0, 93.0, 9.0));
+        assert!(w.overlaps(200.0, 150.0, 1.0));
+    }
+
+    #[test]
+    fn push_out_takes_the_shortest_exit() {
+        let w = wall();
+        // Overlapping the left edge from outside: pushed straight left
+        let (x, y) = w.push_out(95.0, 150.0, 10.0);
+        assert!(close(x, 90.0) && close(y, 150.0));
+        // Center inside, nearest the top side
+        assert_eq!(w.push_out(150.0, 110.0, 10.0), (150.0, 90.0));
+        // Center inside, nearest the right side
+        assert_eq!(w.push_out(295.0, 150.0, 10.0), (310.0, 150.0));
+        // Clear circles are left alone
+        assert_eq!(w.push_out(50.0, 50.0, 10.0), (50.0, 50.0));
+        let (x, y) = w.push_out(150.0, 110.0, 10.0);
+        assert!(!w.overlaps(x, y, 10.0));
+    }
+
+    #[test]
+    fn obstructed_move_slides_and_stops() {
+        let walls = [wall()];
+        // Free move
+        assert_eq!(obstructed_move(&walls, (50.0, 50.0), (60.0, 60.0), 10.0), (60.0, 60.0));
+        // Diagonal into the top face slides along x
+        assert_eq!(obstructed_move(&walls, (150.0, 85.0), (160.0, 95.0), 10.0), (160.0, 85.0));
+        // Straight into the face with nowhere to slide stays put
+        assert_eq!(obstructed_move(&walls, (150.0, 85.0), (150.0, 95.0), 10.0), (150.0, 85.0));
+    }
+
+    #[test]
+    fn obstructed_move_lets_a_stuck_cell_escape() {
+        let walls = [wall()];
+        // Moving out to clear space is allowed straight away
+        assert_eq!(obstructed_move(&walls, (150.0, 110.0), (150.0, 50.0), 10.0), (150.0, 50.0));
+        // Moving deeper in pushes the cell out instead of trapping it
+        let (x, y) = obstructed_move(&walls, (150.0, 110.0), (150.0, 130.0), 10.0);
+        assert!(!walls[0].overlaps(x, y, 10.0));
+    }
+
+    #[test]
+    fn damped_velocity_stops_on_blocked_axes() {
+        let (vx, vy) = damped_velocity((100.0, 100.0), 0.5, (10.0, 10.0), (10.0, 5.0));
+        assert_eq!((vx, vy), (50.0, 0.0));
+        assert_eq!(damped_velocity((1.0, 1.0), 0.5, (0.0, 0.0), (0.0, 0.0)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn edge_touch_reach_is_sum_of_radii() {
+        let cfg = default_config();
+        assert_eq!(cfg.eat_overlap_mode, EatOverlapMode::EdgeTouch);
+        let reach = cfg.eat_reach(30.0, 10.0);
+        assert_eq!(reach, 40.0);
+        // Edible up to and including the boundary
+        assert!(within_reach(0.0, 0.0, 40.0, 0.0, reach));
+        assert!(within_reach(0.0, 0.0, 39.9, 0.0, reach));
+        assert!(!within_reach(0.0, 0.0, 40.01, 0.0, reach));
+    }
+
+    #[test]
+    fn center_cover_reach_needs_engulfing() {
+        let cfg = GameConfig {
+            eat_overlap_mode: EatOverlapMode::CenterCover,
+            eat_cover_factor: 0.5,
+            ..default_config()
+        };
+        let reach = cfg.eat_reach(30.0, 10.0);
+        assert_eq!(reach, 25.0);
+        assert!(within_reach(0.0, 0.0, 15.0, 20.0, reach));
+        assert!(!within_reach(0.0, 0.0, 25.01, 0.0, reach));
+        // A barely overlapping pair that edge-touch allows is out of reach here
+        assert!(!within_reach(0.0, 0.0, 39.9, 0.0, reach));
+        assert_eq!(GameConfig { eat_cover_factor: 0.0, ..cfg }.eat_reach(30.0, 10.0), 30.0);
+        // Never negative, however large the target
+        assert_eq!(GameConfig { eat_cover_factor: 1.0, ..cfg }.eat_reach(10.0, 30.0), 0.0);
+    }
+
+    #[test]
+    fn grid_candidates_grow_far_slower_than_all_pairs() {
+        let cfg = default_config();
+        let mut rng = StdRng::seed_from_u64(30);
+        let n = 5000;
+        let positions: Vec<(f32, f32)> = (0..n).map(|_| cfg.random_position(&mut rng, 0.0)).collect();
+        let mut buckets: HashMap<u64, usize> = HashMap::new();
+        for &(x, y) in &positions {
+            *buckets.entry(grid_bucket(x, y)).or_default() += 1;
+        }
+        // Every entity queries its own neighbourhood, as `resolve_collisions` does
+        let candidates: usize = positions.iter()
+            .map(|&(x, y)| buckets_near(x, y, 50.0).map(|b| buckets.get(&b).copied().unwrap_or(0)).sum::<usize>())
+            .sum();
+        assert!(candidates >= n);
+        assert!(candidates * 50 < n * n, "{candidates} candidates for {n} entities");
+    }
+
+    fn test_player(id: u8, mass: f32) -> Player {
+        let t0 = Timestamp::from_micros_since_unix_epoch(0);
+        Player {
+            identity: Identity::from_byte_array([id; 32]),
+            name: format!("p{id}"),
+            x: 0.0,
+            y: 0.0,
+            radius: default_config().mass_to_radius(mass),
+            mass,
+            color: 0,
+            last_move_micros: 0,
+            spawned_at: t0,
+            spawn_protected_until: t0,
+            invulnerable: false,
+            team_id: None,
+            disconnected_at: None,
+            online_since: t0,
+            last_active_at: t0,
+            boost_until: t0,
+            shield_until: t0,
+            teleport_ready_at: t0,
+            score: 0,
+            view_radius: BASE_VIEW_RADIUS,
+            vx: 0.0,
+            vy: 0.0,
+        }
+    }
+
+    #[test]
+    fn disconnected_players_never_hunt() {
+        let ghost = Player { disconnected_at: Some(Timestamp::from_micros_since_unix_epoch(1)), ..test_player(1, 500.0) };
+        let order = hunting_order([ghost, test_player(2, 50.0)]);
+        assert_eq!(order, vec![test_player(2, 50.0).identity]);
+    }
 
-/// Eat another player using a split cell.
-/// Cell must belong to the caller, be ≥10% heavier than the target, and overlapping.
-/// The target's entire mass (including any split cells) is absorbed into this cell.
-#[spacetimedb::reducer]
-pub fn eat_player_cell(ctx: &ReducerContext, cell_id: u64, target_identity: Identity) {
-    let eater_id = ctx.sender();
-    if eater_id == target_identity { return; }
+    #[test]
+    fn obstructed_move_does_not_tunnel_through_thin_walls() {
+        let thin = [Obstacle { id: 1, x: 100.0, y: 0.0, width: 5.0, height: 1000.0 }];
+        // Both ends are clear of the wall but the path crosses it
+        assert_eq!(obstructed_move(&thin, (50.0, 500.0), (200.0, 500.0), 10.0), (50.0, 500.0));
+        // A diagonal jump across keeps only the vertical component
+        assert_eq!(obstructed_move(&thin, (50.0, 500.0), (200.0, 520.0), 10.0), (50.0, 520.0));
+    }
 
-    let Some(cell) = ctx.db.player_cell().cell_id().find(cell_id) else { return; };
-    if cell.player_identity != eater_id { return; }
+    #[test]
+    fn swept_path_clips_corners_but_not_near_misses() {
+        let w = wall();
+        // Passes the top-left corner (100, 100) at about 7, with both ends clear
+        assert!(w.blocks_path((130.0, 60.0), (60.0, 130.0), 10.0));
+        // Passes it at about 14
+        assert!(!w.blocks_path((120.0, 60.0), (60.0, 120.0), 10.0));
+        assert!(!w.blocks_path((0.0, 50.0), (400.0, 50.0), 10.0));
+        assert!(w.blocks_path((0.0, 150.0), (400.0, 150.0), 10.0));
+    }
 
-    let Some(target) = ctx.db.player().identity().find(target_identity) else { return; };
+    #[test]
+    fn scatter_split_conserves_mass() {
+        for mass in [0.5, GOLDEN_FOOD_MASS, 12.0, 99.0, 10_000.0] {
+            let (count, pellet_mass) = scatter_split(mass);
+            assert!((1..=KILL_SCATTER_MAX).contains(&count));
+            assert!(close(count as f32 * pellet_mass, mass));
+        }
+        // Small drops use golden-sized pellets; huge ones hit the cap
+        assert_eq!(scatter_split(12.0).0, 3);
+        assert_eq!(scatter_split(10_000.0), (KILL_SCATTER_MAX, 10_000.0 / KILL_SCATTER_MAX as f32));
+        assert_eq!(scatter_split(0.0), (0, 0.0));
+        assert_eq!(scatter_split(-3.0), (0, 0.0));
+    }
 
-    if cell.mass < target.mass * 1.1 { return; }
+    #[test]
+    fn split_cells_threaten_cells_they_can_absorb() {
+        // Viewer: main cell counted at its total of 300, one 50-mass split cell
+        let prey = [(0.0, 0.0, 300.0), (200.0, 0.0, 50.0)];
+        // A 100-mass split cell can't take the main cell but can take the split one
+        assert_eq!(nearest_edible((250.0, 0.0, 100.0), &prey), Some(50.0));
+        // A 400-mass cell could eat either; the nearer one counts
+        assert_eq!(nearest_edible((-100.0, 0.0, 400.0), &prey), Some(100.0));
+        // Too far away, or too small for anything
+        assert_eq!(nearest_edible((200.0 + THREAT_RADIUS + 1.0, 0.0, 100.0), &prey), None);
+        assert_eq!(nearest_edible((210.0, 0.0, 50.0), &prey), None);
+    }
 
-    let dx = cell.x - target.x;
-    let dy = cell.y - target.y;
-    let dist_sq = dx * dx + dy * dy;
-    if dist_sq > (cell.radius * 2.0) * (cell.radius * 2.0) { return; }
+    #[test]
+    fn speed_view_and_decay_follow_the_configured_initial_mass() {
+        let cfg = GameConfig { initial_mass: 40.0, ..default_config() };
+        assert!(close(cell_speed(&cfg, 40.0), BASE_SPEED));
+        assert!(close(view_radius(&cfg, 40.0), BASE_VIEW_RADIUS));
+        assert!(close(view_radius(&cfg, 40.0 * 16.0), BASE_VIEW_RADIUS * 2.0));
+        assert!(close(1.0 - decayed_mass(&cfg, 40.0) / 40.0, 1.0 - cfg.decay_rate));
+    }
 
-    // Absorb target's split-cell mass too
-    let split_mass: f32 = ctx.db.player_cell().iter()
-        .filter(|c| c.player_identity == target_identity)
-        .map(|c| c.mass)
-        .sum();
+    #[test]
+    fn consumed_or_dead_victims_are_not_eaten_again() {
+        let now = Timestamp::from_micros_since_unix_epoch(10_000_000);
+        assert!(still_consumable(&test_player(1, 80.0), now));
+        // Dead rows
+        assert!(!still_consumable(&test_player(1, 0.0), now));
+        assert!(!still_consumable(&test_player(1, f32::NAN), now));
+        // Respawned earlier in this tick: a new life, not the victim the eater saw
+        let respawned = Player { spawned_at: now, ..test_player(1, 80.0) };
+        assert!(!still_consumable(&respawned, now));
+    }
 
-    let new_cell_mass = cell.mass + target.mass + split_mass;
-    ctx.db.player_cell().cell_id().update(PlayerCell {
-        mass: new_cell_mass,
-        radius: mass_to_radius(new_cell_mass),
-        ..cell
-    });
+    #[test]
+    fn playtime_counts_only_connected_stretches() {
+        let at = Timestamp::from_micros_since_unix_epoch;
+        // Spawned at 0, dropped at 30s: 30s credited on disconnect
+        let player = test_player(1, 100.0);
+        assert_eq!(online_micros(&player, at(30_000_000)), 30_000_000);
+        // While gone nothing more accrues, however long removal takes
+        let gone = Player { disconnected_at: Some(at(30_000_000)), ..test_player(1, 100.0) };
+        assert_eq!(online_micros(&gone, at(45_000_000)), 0);
+        // Back at 40s and removed at 50s: only the last 10s
+        let back = Player { online_since: at(40_000_000), ..player };
+        assert_eq!(online_micros(&back, at(50_000_000)), 10_000_000);
+    }
 
-    ctx.db.player().identity().delete(target_identity);
-    delete_player_cells(ctx, target_identity);
-}
+    #[test]
+    fn one_shot_schedules_requeue_only_while_paused() {
+        let now = Timestamp::from_micros_since_unix_epoch(5_000_000);
+        let running = default_config();
+        assert_eq!(paused_retry(&running, now), None);
+        let paused = GameConfig { paused: true, ..default_config() };
+        assert_eq!(paused_retry(&paused, now), Some(Timestamp::from_micros_since_unix_epoch(6_000_000)));
+    }
 
-/// Eat an ejected mass pellet.
-#[spacetimedb::reducer]
-pub fn eat_ejected_mass(ctx: &ReducerContext, mass_id: u64) {
-    let identity = ctx.sender();
-    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
-    let Some(em) = ctx.db.ejected_mass().id().find(mass_id) else { return; };
+    fn test_cell(owner: u8, x: f32, y: f32, mass: f32) -> PlayerCell {
+        PlayerCell {
+            cell_id: 1,
+            player_identity: Identity::from_byte_array([owner; 32]),
+            x,
+            y,
+            radius: default_config().mass_to_radius(mass),
+            mass,
+            last_move_micros: 0,
+            merge_at: Timestamp::from_micros_since_unix_epoch(0),
+            vx: 0.0,
+            vy: 0.0,
+            color: 0,
+        }
+    }
 
-    let dx = em.x - player.x;
-    let dy = em.y - player.y;
-    let dist_sq = dx * dx + dy * dy;
-    let eat_dist = player.radius + em.radius;
-    if dist_sq > (eat_dist * 2.0) * (eat_dist * 2.0) { return; }
+    fn test_eject(ejector: u8, x: f32, y: f32) -> EjectedMass {
+        EjectedMass {
+            id: 1,
+            x,
+            y,
+            radius: default_config().mass_to_radius(EJECT_MASS_AMOUNT),
+            mass: EJECT_MASS_AMOUNT,
+            ejected_at: Timestamp::from_micros_since_unix_epoch(0),
+            vx: 0.0,
+            vy: 0.0,
+            color: 0,
+            ejector: Identity::from_byte_array([ejector; 32]),
+        }
+    }
 
-    ctx.db.ejected_mass().id().delete(mass_id);
-    let new_mass = player.mass + em.mass;
-    ctx.db.player().identity().update(Player {
-        mass: new_mass,
-        radius: mass_to_radius(new_mass),
-        ..player
-    });
-}
+    #[test]
+    fn split_cells_reach_ejected_mass_from_their_own_position() {
+        let main = test_player(1, 400.0);
+        let cell = test_cell(1, 600.0, 0.0, 200.0);
+        let em = test_eject(2, 600.0 + cell.radius, 0.0);
+        let now = Timestamp::from_micros_since_unix_epoch(0);
+        assert!(can_eat_eject(main.identity, (cell.x, cell.y, cell.radius), &em, now));
+        assert!(!can_eat_eject(main.identity, (main.x, main.y, main.radius), &em, now));
+    }
 
-// ---------------------------------------------------------------------------
-// Decay (scheduled)
-// ---------------------------------------------------------------------------
+    #[test]
+    fn split_skips_light_cells_and_stops_at_the_cell_cap() {
+        let cfg = default_config();
+        assert_eq!(cells_to_split(&cfg, &[400.0, 100.0, 500.0]), vec![0, 2]);
+        assert_eq!(cells_to_split(&cfg, &[100.0, 400.0]), vec![1]);
+        let crowded = vec![400.0; MAX_CELLS - 2];
+        assert_eq!(cells_to_split(&cfg, &crowded), vec![0, 1]);
+    }
 
-#[spacetimedb::reducer]
-pub fn decay_mass(_ctx: &ReducerContext, _schedule: MassDecaySchedule) {
-    for player in _ctx.db.player().iter() {
-        if player.mass > INITIAL_MASS {
-            let new_mass = (player.mass * MASS_DECAY_RATE).max(INITIAL_MASS);
-            _ctx.db.player().identity().update(Player {
-                mass: new_mass,
-                radius: mass_to_radius(new_mass),
-                ..player
-            });
+    #[test]
+    fn virus_burst_splits_into_equal_shares() {
+        let (fragments, piece_mass) = virus_burst(800.0, 1);
+        assert_eq!(fragments, VIRUS_SPLIT_PIECES - 1);
+        assert!(close(piece_mass, 800.0 / VIRUS_SPLIT_PIECES as f32));
+        let main_mass = 800.0 - piece_mass * fragments as f32;
+        assert!(close(main_mass, piece_mass));
+    }
+
+    #[test]
+    fn virus_burst_never_makes_fragments_below_the_minimum() {
+        let (fragments, piece_mass) = virus_burst(MIN_FRAGMENT_MASS * 3.5, 1);
+        assert_eq!(fragments, 2);
+        assert!(piece_mass >= MIN_FRAGMENT_MASS);
+        assert_eq!(virus_burst(MIN_FRAGMENT_MASS * 1.5, 1).0, 0);
+    }
+
+    #[test]
+    fn fed_virus_shoots_along_the_pellet_path() {
+        let cfg = default_config();
+        let virus = Virus { id: 1, x: 1000.0, y: 1000.0, radius: 30.0, mass: VIRUS_MASS, feed_count: VIRUS_FEED_LIMIT - 1 };
+        let (x, y) = shot_position(&cfg, &virus, (950.0, 1000.0));
+        assert!(close(x, 1000.0 + VIRUS_SHOOT_DISTANCE) && close(y, 1000.0));
+        let (x, y) = shot_position(&cfg, &virus, (1000.0, 1040.0));
+        assert!(close(x, 1000.0) && close(y, 1000.0 - VIRUS_SHOOT_DISTANCE));
+
+        let edge = Virus { x: cfg.width() - 120.0, ..virus };
+        let (x, _) = shot_position(&cfg, &edge, (edge.x - 10.0, 1000.0));
+        assert!(x <= cfg.width() - 100.0);
+    }
+
+    #[test]
+    fn ejected_mass_expires_once_its_lifetime_passes() {
+        let em = test_eject(1, 0.0, 0.0);
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        assert!(!is_expired(&em, at(0)));
+        assert!(!is_expired(&em, at(EJECTED_MASS_LIFETIME_MICROS - 1)));
+        assert!(is_expired(&em, at(EJECTED_MASS_LIFETIME_MICROS)));
+    }
+
+    #[test]
+    fn food_maintainer_tops_up_to_max_food() {
+        let cfg = default_config();
+        let max = cfg.max_food as u64;
+        assert_eq!(food_refill(&cfg, max - 50), 50);
+        assert_eq!(food_refill(&cfg, max), 0);
+        assert_eq!(food_refill(&cfg, max + 5), 0);
+    }
+
+    #[test]
+    fn leaderboard_keeps_the_ten_heaviest_in_order() {
+        let players = (1..=12).map(|id| test_player(id, id as f32 * 100.0));
+        let split_mass = HashMap::from([(Identity::from_byte_array([1; 32]), 5000.0)]);
+        let board = rank_players(players, &split_mass);
+        assert_eq!(board.len(), LEADERBOARD_SIZE);
+        assert_eq!(board[0].identity, Identity::from_byte_array([1; 32]));
+        assert!(close(board[0].mass, 5100.0));
+        assert_eq!(board[1].identity, Identity::from_byte_array([12; 32]));
+        assert!(board.windows(2).all(|w| w[0].mass >= w[1].mass));
+        assert_eq!(board.iter().map(|e| e.rank).collect::<Vec<_>>(), (1..=10).collect::<Vec<_>>());
+        assert!(board.iter().all(|e| e.identity != Identity::from_byte_array([2; 32])));
+    }
+
+    #[test]
+    fn leaderboard_resorts_only_when_the_top_ten_can_change() {
+        let board = rank_players((1..=12).map(|id| test_player(id, id as f32 * 100.0)), &HashMap::new());
+        let outsider = Identity::from_byte_array([2; 32]);
+        assert!(!leaderboard_affected(&board, outsider, 250.0));
+        assert!(leaderboard_affected(&board, outsider, 350.0));
+        assert!(leaderboard_affected(&board, Identity::from_byte_array([3; 32]), 0.0));
+        assert!(leaderboard_affected(&board[..3], outsider, 1.0));
+    }
+
+    #[test]
+    fn spawn_protection_lapses_after_its_window() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let fresh = Player { spawn_protected_until: at(SPAWN_PROTECTION_MICROS), ..test_player(1, INITIAL_MASS) };
+        assert!(is_uneatable(&fresh, at(0)));
+        assert!(is_uneatable(&fresh, at(SPAWN_PROTECTION_MICROS - 1)));
+        assert!(!is_uneatable(&fresh, at(SPAWN_PROTECTION_MICROS)));
+    }
+
+    #[test]
+    fn only_players_sharing_a_team_id_are_teammates() {
+        let red = |id| Player { team_id: Some(1), ..test_player(id, 100.0) };
+        let blue = Player { team_id: Some(2), ..test_player(3, 100.0) };
+        assert!(same_team(&red(1), &red(2)));
+        assert!(!same_team(&red(1), &blue));
+        assert!(!same_team(&test_player(4, 100.0), &test_player(5, 100.0)));
+    }
+
+    #[test]
+    fn bots_flee_threats_before_chasing_food() {
+        let bot = Player { x: 1000.0, y: 1000.0, ..test_player(1, 100.0) };
+        let food = [(1100.0, 1000.0), (1000.0, 1050.0), (1010.0, 1000.0 + BOT_SIGHT_RADIUS * 2.0)];
+        assert_eq!(bot_target(&bot, std::iter::empty(), food.into_iter()), Some((1000.0, 1050.0)));
+
+        let hunter = Player { x: 900.0, y: 1000.0, ..test_player(2, 500.0) };
+        let prey = Player { x: 1020.0, y: 1000.0, ..test_player(3, 50.0) };
+        assert_eq!(bot_target(&bot, [prey, hunter].into_iter(), food.into_iter()), Some((1100.0, 1000.0)));
+
+        let far = Player { x: 1000.0 - BOT_SIGHT_RADIUS * 2.0, y: 1000.0, ..test_player(2, 500.0) };
+        assert_eq!(bot_target(&bot, [far].into_iter(), std::iter::empty()), None);
+    }
+
+    #[test]
+    fn bot_identities_are_stable_and_distinct() {
+        assert_eq!(bot_identity(3), bot_identity(3));
+        assert_ne!(bot_identity(0), bot_identity(1));
+    }
+
+    #[test]
+    fn death_events_name_killer_and_victim() {
+        assert_eq!(death_payload(Some("Big \"B\""), "small"), "{\"killer\":\"Big \\\"B\\\"\",\"victim\":\"small\"}");
+        assert_eq!(death_payload(None, "small"), "{\"killer\":null,\"victim\":\"small\"}");
+    }
+
+    #[test]
+    fn disconnected_players_keep_their_mass_through_the_grace_period() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let left = Player { disconnected_at: Some(at(1_000)), ..test_player(1, 800.0) };
+        assert!(!grace_expired(&left, at(1_000 + RECONNECT_GRACE_MICROS - 1)));
+        assert!(grace_expired(&left, at(1_000 + RECONNECT_GRACE_MICROS)));
+        assert!(!grace_expired(&test_player(2, 800.0), at(1_000 + RECONNECT_GRACE_MICROS)));
+    }
+
+    #[test]
+    fn ejected_mass_coasts_slows_and_settles() {
+        let cfg = default_config();
+        let dt = MOTION_TICK_MICROS as f32 / 1_000_000.0;
+        let mut em = EjectedMass { x: 1000.0, y: 1000.0, vx: EJECT_IMPULSE, ..test_eject(1, 0.0, 0.0) };
+        let ((x, y), (vx, vy)) = drift_step(&cfg, &em);
+        assert!(close(x, 1000.0 + EJECT_IMPULSE * dt) && close(y, 1000.0));
+        assert!(vx > 0.0 && vx < EJECT_IMPULSE && vy == 0.0);
+
+        let mut ticks = 0;
+        while em.vx != 0.0 {
+            let ((x, y), (vx, vy)) = drift_step(&cfg, &em);
+            em = EjectedMass { x, y, vx, vy, ..em };
+            ticks += 1;
+            assert!(ticks < 100, "pellet never came to rest");
         }
+        assert!(em.x > 1000.0);
     }
-    // Also decay split cells (floor: half the starting mass)
-    let min_cell_mass = INITIAL_MASS / 2.0;
-    for cell in _ctx.db.player_cell().iter() {
-        if cell.mass > min_cell_mass {
-            let new_mass = (cell.mass * MASS_DECAY_RATE).max(min_cell_mass);
-            _ctx.db.player_cell().cell_id().update(PlayerCell {
-                mass: new_mass,
-                radius: mass_to_radius(new_mass),
-                ..cell
-            });
+
+    #[test]
+    fn zone_drains_cells_outside_it_until_they_are_lost() {
+        let zone = SafeZone { id: 0, center_x: 1000.0, center_y: 1000.0, radius: 300.0, shrink_rate: 5.0 };
+        assert!(zone.contains(1000.0, 1300.0));
+        assert!(!zone.contains(1300.0, 1300.0));
+
+        assert!(close(zone_drained(100.0).unwrap(), 100.0 * (1.0 - ZONE_DAMAGE_RATE)));
+        assert_eq!(zone_drained(ZONE_MIN_MASS), None);
+        let mut mass = 200.0;
+        let mut ticks = 0;
+        while let Some(left) = zone_drained(mass) {
+            mass = left;
+            ticks += 1;
         }
+        assert!(ticks > 0 && mass >= ZONE_MIN_MASS);
     }
-}
 
-// ---------------------------------------------------------------------------
-// Eject mass
-// ---------------------------------------------------------------------------
+    #[test]
+    fn other_players_split_cells_can_be_eaten() {
+        let cfg = default_config();
+        let now = Timestamp::from_micros_since_unix_epoch(0);
+        let eater = test_player(1, 500.0);
+        let eater = (eater.x, eater.y, eater.radius, eater.mass);
+        let small = test_cell(2, 10.0, 0.0, 100.0);
+        assert!(can_eat_cell(&cfg, eater, &small, false, now));
+        let heavy = test_cell(2, 10.0, 0.0, 480.0);
+        assert!(!can_eat_cell(&cfg, eater, &heavy, false, now));
+        let distant = test_cell(2, 200.0, 0.0, 100.0);
+        assert!(!can_eat_cell(&cfg, eater, &distant, false, now));
+    }
 
-#[spacetimedb::reducer]
-pub fn eject_mass(ctx: &ReducerContext, dir_x: f32, dir_y: f32) {
-    let identity = ctx.sender();
-    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    #[test]
+    fn own_split_cells_wait_for_their_merge_timer() {
+        let cfg = default_config();
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let main = test_player(1, 100.0);
+        let main = (main.x, main.y, main.radius, main.mass);
+        let cell = PlayerCell { merge_at: at(MERGE_DELAY_MICROS), ..test_cell(1, 5.0, 0.0, 400.0) };
+        assert!(!can_eat_cell(&cfg, main, &cell, true, at(MERGE_DELAY_MICROS - 1)));
+        // Past the timer own cells merge regardless of which is heavier
+        assert!(can_eat_cell(&cfg, main, &cell, true, at(MERGE_DELAY_MICROS)));
+    }
 
-    if player.mass <= INITIAL_MASS + EJECT_MASS_AMOUNT { return; }
+    #[test]
+    fn rate_limit_enforces_each_cooldown_separately() {
+        let mut limit = RateLimit {
+            identity: Identity::from_byte_array([1; 32]),
+            last_eject_micros: i64::MIN,
+            last_split_micros: i64::MIN,
+        };
+        assert!(limit.try_take(RateLimitedAction::Eject, 1_000, EJECT_COOLDOWN_MICROS));
+        assert!(!limit.try_take(RateLimitedAction::Eject, 1_000 + EJECT_COOLDOWN_MICROS - 1, EJECT_COOLDOWN_MICROS));
+        assert!(limit.try_take(RateLimitedAction::Split, 1_001, SPLIT_COOLDOWN_MICROS));
+        assert!(limit.try_take(RateLimitedAction::Eject, 1_000 + EJECT_COOLDOWN_MICROS, EJECT_COOLDOWN_MICROS));
+        assert_eq!(limit.last_split_micros, 1_001);
+    }
 
-    let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
-    if len < 0.001 { return; }
-    let nx = dir_x / len;
-    let ny = dir_y / len;
+    #[test]
+    fn admins_are_the_publisher_plus_valid_configured_identities() {
+        let publisher = Identity::from_byte_array([1; 32]);
+        let extra = "02".repeat(32);
+        let admins = admin_identities(publisher, &[&extra, "not hex", &"01".repeat(32), &extra]);
+        assert_eq!(admins, vec![publisher, Identity::from_byte_array([2; 32])]);
+    }
 
-    let eject_dist = 300.0_f32;
-    let ej_x = (player.x + nx * eject_dist).clamp(20.0, WORLD_WIDTH - 20.0);
-    let ej_y = (player.y + ny * eject_dist).clamp(20.0, WORLD_HEIGHT - 20.0);
+    #[test]
+    fn chat_messages_are_cleaned_and_length_capped() {
+        assert_eq!(clean_chat("  gg\u{7} wp\n "), Some("gg wp".to_string()));
+        assert_eq!(clean_chat(" \t\n"), None);
+        assert_eq!(clean_chat(&"é".repeat(MAX_CHAT_LEN)).map(|t| t.chars().count()), Some(MAX_CHAT_LEN));
+        assert_eq!(clean_chat(&"a".repeat(MAX_CHAT_LEN + 1)), None);
+    }
 
-    let new_mass = player.mass - EJECT_MASS_AMOUNT;
-    ctx.db.player().identity().update(Player {
-        mass: new_mass,
-        radius: mass_to_radius(new_mass),
-        ..player
-    });
+    #[test]
+    fn mass_transfers_only_between_touching_teammates() {
+        let cfg = default_config();
+        let giver = Player { team_id: Some(1), ..test_player(1, 500.0) };
+        let mate = Player { team_id: Some(1), x: 30.0, ..test_player(2, 200.0) };
+        assert_eq!(transferable(&cfg, &giver, &mate, 100.0), Some(100.0));
+        assert_eq!(transferable(&cfg, &giver, &mate, 500.0 - cfg.initial_mass + 1.0), None);
 
-    ctx.db.ejected_mass().insert(EjectedMass {
-        id: 0,
-        x: ej_x,
-        y: ej_y,
-        radius: mass_to_radius(EJECT_MASS_AMOUNT),
-        mass: EJECT_MASS_AMOUNT,
-    });
-}
+        let rival = Player { team_id: Some(2), ..test_player(3, 200.0) };
+        assert_eq!(transferable(&cfg, &giver, &rival, 100.0), None);
+        let far = Player { x: 500.0, ..mate };
+        assert_eq!(transferable(&cfg, &giver, &far, 100.0), None);
 
-// ---------------------------------------------------------------------------
-// Split / merge
-// ---------------------------------------------------------------------------
+        let full = Player { team_id: Some(1), ..test_player(4, cfg.max_mass - 40.0) };
+        assert_eq!(transferable(&cfg, &giver, &full, 100.0), Some(40.0));
+    }
 
-/// Split the player's cell in two. The split half is inserted as a PlayerCell row,
-/// enabling both halves to move independently toward the cursor.
-#[spacetimedb::reducer]
-pub fn split_cell(ctx: &ReducerContext, dir_x: f32, dir_y: f32) {
-    let identity = ctx.sender();
-    let Some(player) = ctx.db.player().identity().find(identity) else { return; };
+    #[test]
+    fn power_ups_last_for_their_duration() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let boosted = with_powerup(test_player(1, 100.0), PowerUpKind::SpeedBoost, at(1_000));
+        assert!(is_boosted(&boosted, at(1_000 + BOOST_DURATION_MICROS - 1)));
+        assert!(!is_boosted(&boosted, at(1_000 + BOOST_DURATION_MICROS)));
+        assert!(!is_uneatable(&boosted, at(1_000)));
 
-    // Require minimum mass and must not already be split
-    if player.mass < MIN_SPLIT_MASS { return; }
-    let already_split = ctx.db.player_cell().iter().any(|c| c.player_identity == identity);
-    if already_split { return; }
+        let shielded = with_powerup(test_player(1, 100.0), PowerUpKind::Shield, at(1_000));
+        assert!(is_uneatable(&shielded, at(1_000 + SHIELD_DURATION_MICROS - 1)));
+        assert!(!is_uneatable(&shielded, at(1_000 + SHIELD_DURATION_MICROS)));
+        assert!(!is_boosted(&shielded, at(1_000)));
+    }
 
-    let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
-    if len < 0.001 { return; }
-    let nx = dir_x / len;
-    let ny = dir_y / len;
+    #[test]
+    fn portals_send_players_to_the_opposite_end() {
+        let portals = [Portal { id: 1, entry_x: 100.0, entry_y: 100.0, exit_x: 2000.0, exit_y: 1500.0, radius: PORTAL_RADIUS }];
+        let at = |x, y| Player { x, y, ..test_player(1, 100.0) };
+        assert_eq!(portal_destination(&portals, &at(120.0, 100.0)), Some((2000.0, 1500.0)));
+        assert_eq!(portal_destination(&portals, &at(2000.0, 1530.0)), Some((100.0, 100.0)));
+        assert_eq!(portal_destination(&portals, &at(500.0, 500.0)), None);
+    }
 
-    let half_mass = player.mass / 2.0;
-    let split_offset = mass_to_radius(half_mass) * 2.5;
-    let split_x = (player.x + nx * split_offset).clamp(50.0, WORLD_WIDTH - 50.0);
-    let split_y = (player.y + ny * split_offset).clamp(50.0, WORLD_HEIGHT - 50.0);
+    #[test]
+    fn minimap_blips_bucket_position_and_mass() {
+        let cfg = default_config();
+        let player = Player { x: 250.0, y: 1999.0, ..test_player(1, 100.0) };
+        let blip = minimap_blip(&cfg, &player, cfg.initial_mass);
+        assert_eq!((blip.grid_x, blip.grid_y, blip.mass_bucket), (2, 19, 0));
+        assert_eq!(minimap_blip(&cfg, &player, cfg.initial_mass * 4.0).mass_bucket, 2);
+        assert_eq!(minimap_blip(&cfg, &player, cfg.initial_mass / 2.0).mass_bucket, 0);
+        let off_edge = Player { x: -5.0, ..player };
+        assert_eq!(minimap_blip(&cfg, &off_edge, cfg.initial_mass).grid_x, 0);
+    }
 
-    // Reduce main cell to half mass
-    ctx.db.player().identity().update(Player {
-        mass: half_mass,
-        radius: mass_to_radius(half_mass),
-        ..player
-    });
+    #[test]
+    fn no_merge_pass_is_queued_once_the_cells_are_gone() {
+        assert_eq!(next_merge_at(Vec::new()), None);
+    }
 
-    // Create the split half as a separate controllable row
-    ctx.db.player_cell().insert(PlayerCell {
-        cell_id: 0,
-        player_identity: identity,
-        x: split_x,
-        y: split_y,
-        radius: mass_to_radius(half_mass),
-        mass: half_mass,
-    });
+    #[test]
+    fn repeated_splits_share_one_merge_pass_at_the_earliest_timer() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let cells = [3_000, 1_000, 2_000].map(|micros| PlayerCell { merge_at: at(micros), ..test_cell(1, 0.0, 0.0, 100.0) });
+        assert_eq!(next_merge_at(cells), Some(at(1_000)));
+    }
 
-    // Schedule merge back after 10 seconds
-    let merge_time = ctx.timestamp + TimeDuration::from_micros(10_000_000);
-    ctx.db.split_merge_schedule().insert(SplitMergeSchedule {
-        scheduled_id: 0,
-        scheduled_at: merge_time.into(),
-        player_identity: identity,
-    });
-}
+    #[test]
+    fn round_goes_to_the_heaviest_total_mass() {
+        // The lighter main cell wins once its split cells are counted
+        let winner = round_winner([(900.0, test_player(1, 300.0)), (500.0, test_player(2, 500.0))]);
+        assert_eq!(winner.map(|p| p.identity), Some(Identity::from_byte_array([1; 32])));
+        assert!(round_winner(Vec::new()).is_none());
 
-/// Merge all split cells back into the main cell.
-#[spacetimedb::reducer]
-pub fn merge_split(_ctx: &ReducerContext, schedule: SplitMergeSchedule) {
-    let identity = schedule.player_identity;
+        assert_eq!(round_end_payload(7, Some("p1")), "{\"round_id\":7,\"winner\":\"p1\"}");
+        assert_eq!(round_end_payload(8, None), "{\"round_id\":8,\"winner\":null}");
+    }
 
-    let cells: Vec<PlayerCell> = _ctx.db.player_cell().iter()
-        .filter(|c| c.player_identity == identity)
-        .collect();
+    #[test]
+    fn spectators_follow_only_spawned_players() {
+        let live = Identity::from_byte_array([1; 32]);
+        let is_player = |identity| identity == live;
+        assert!(can_follow(None, is_player));
+        assert!(can_follow(Some(live), is_player));
+        assert!(!can_follow(Some(Identity::from_byte_array([2; 32])), is_player));
+    }
+
+    #[test]
+    fn cells_move_only_for_their_living_owner() {
+        let cell = test_cell(1, 0.0, 0.0, 100.0);
+        assert!(live_owner(Some(test_player(1, 100.0)), &cell).is_some());
+        assert!(live_owner(Some(test_player(2, 100.0)), &cell).is_none());
+        assert!(live_owner(None, &cell).is_none());
+    }
 
-    if cells.is_empty() { return; }
+    #[test]
+    fn world_resizes_with_player_count_within_bounds() {
+        let cfg = default_config();
+        // The default 3000x3000 world is exactly right for ten players
+        assert_eq!(resize_scale(&cfg, 10), None);
+        assert_eq!(resize_scale(&cfg, 11), None);
+        let grown = resize_scale(&cfg, 40).unwrap();
+        assert!(close(grown, 2.0));
+        assert!(close(resize_scale(&cfg, 0).unwrap() * cfg.width(), MIN_AUTO_WORLD_SIZE));
+        assert!(close(resize_scale(&cfg, 10_000).unwrap() * cfg.width(), MAX_AUTO_WORLD_SIZE));
+    }
 
-    let split_mass: f32 = cells.iter().map(|c| c.mass).sum();
+    #[test]
+    fn snapshot_totals_players_and_food() {
+        let snapshot = build_snapshot(Timestamp::from_micros_since_unix_epoch(0), &[100.0, 250.0], &[50.0], 180, 0, 0);
+        assert_eq!(snapshot.player_count, 2);
+        assert!(close(snapshot.total_mass, 400.0));
+        assert_eq!(snapshot.food_count, 180);
+    }
 
-    if let Some(player) = _ctx.db.player().identity().find(identity) {
-        let merged_mass = player.mass + split_mass;
-        _ctx.db.player().identity().update(Player {
-            mass: merged_mass,
-            radius: mass_to_radius(merged_mass),
-            ..player
-        });
+    #[test]
+    fn spawns_land_clear_of_a_giant() {
+        let cfg = default_config();
+        let giant = (cfg.width() / 2.0, cfg.height() / 2.0, 800.0);
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (x, y) = safest_position(&cfg, &[giant], &mut rng);
+            assert!(!within_reach(x, y, giant.0, giant.1, giant.2), "seed {seed} spawned at ({x}, {y})");
+        }
     }
 
-    for cell in cells {
-        _ctx.db.player_cell().cell_id().delete(cell.cell_id);
+    #[test]
+    fn spawn_color_honors_the_palette_and_falls_back() {
+        let palette = [0xff0000, 0x00ff00, 0x0000ff];
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(spawn_color(0x00ff00, Some(0xff0000), &palette, &mut rng), 0x00ff00);
+        assert_eq!(spawn_color(0x123456, Some(0x0000ff), &palette, &mut rng), 0x0000ff);
+        assert!(palette.contains(&spawn_color(0x123456, Some(0x654321), &palette, &mut rng)));
+        assert_eq!(spawn_color(0x123456, None, &[], &mut rng), DEFAULT_PLAYER_COLORS[0]);
     }
-}
 
-// ---------------------------------------------------------------------------
-// Helper
-// ---------------------------------------------------------------------------
+    #[test]
+    fn virus_burst_respects_the_cell_cap() {
+        let (fragments, piece_mass) = virus_burst(2000.0, MAX_CELLS - 3);
+        assert_eq!(fragments, 3);
+        // Shares stay the usual size; the main cell keeps what couldn't split off
+        assert!(close(piece_mass, 2000.0 / VIRUS_SPLIT_PIECES as f32));
+        assert_eq!(virus_burst(2000.0, MAX_CELLS).0, 0);
+    }
 
-fn delete_player_cells(ctx: &ReducerContext, identity: Identity) {
-    let ids: Vec<u64> = ctx.db.player_cell().iter()
-        .filter(|c| c.player_identity == identity)
-        .map(|c| c.cell_id)
-        .collect();
-    for id in ids {
-        ctx.db.player_cell().cell_id().delete(id);
+    #[test]
+    fn decay_waits_for_two_connected_humans() {
+        let bot = Identity::from_byte_array([9; 32]);
+        let is_bot = |identity| identity == bot;
+        let ghost = Player { disconnected_at: Some(Timestamp::from_micros_since_unix_epoch(1)), ..test_player(2, 500.0) };
+        assert!(!decay_applies([test_player(1, 500.0), test_player(9, 500.0), ghost], is_bot));
+        assert!(!decay_applies(Vec::new(), is_bot));
+        assert!(decay_applies([test_player(1, 500.0), test_player(3, 500.0)], is_bot));
+    }
+
+    #[test]
+    fn decay_settings_are_validated_and_the_rate_applies() {
+        assert!(valid_decay(DECAY_INTERVAL_MICROS, MASS_DECAY_RATE));
+        assert!(valid_decay(MIN_DECAY_INTERVAL_MICROS, 1.0));
+        assert!(!valid_decay(MIN_DECAY_INTERVAL_MICROS - 1, MASS_DECAY_RATE));
+        for rate in [0.0, -0.5, 1.01, f32::NAN] {
+            assert!(!valid_decay(DECAY_INTERVAL_MICROS, rate));
+        }
+
+        let gentle = GameConfig { decay_rate: 0.999, ..default_config() };
+        let harsh = GameConfig { decay_rate: 0.99, ..default_config() };
+        assert!(decayed_mass(&harsh, 500.0) < decayed_mass(&gentle, 500.0));
+    }
+
+    #[test]
+    fn leaderboard_ties_go_to_the_earlier_spawn_then_identity() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let late = Player { spawned_at: at(2_000), ..test_player(1, 300.0) };
+        let early = Player { spawned_at: at(1_000), ..test_player(2, 300.0) };
+        let twin_b = Player { spawned_at: at(3_000), ..test_player(4, 300.0) };
+        let twin_a = Player { spawned_at: at(3_000), ..test_player(3, 300.0) };
+        let board = rank_players([twin_b, late, twin_a, early], &HashMap::new());
+        let order: Vec<u8> = board.iter().map(|e| e.identity.to_byte_array()[0]).collect();
+        assert_eq!(order, vec![2, 1, 3, 4]);
+    }
+
+    #[test]
+    fn nearby_food_is_chosen_by_distance_not_by_id() {
+        let player = test_player(1, 100.0);
+        let pellet = |id, x| FoodPellet { id, x, y: 0.0, radius: FOOD_RADIUS, mass: FOOD_MASS };
+        let reach = player.radius + FOOD_RADIUS;
+        let food = [pellet(1, reach - 1.0), pellet(2, 5.0), pellet(3, reach + 1.0)];
+        assert_eq!(closest_food(&player, food.into_iter()).map(|f| f.id), Some(2));
+        let out_of_reach = [pellet(3, reach + 1.0)];
+        assert!(closest_food(&player, out_of_reach.into_iter()).is_none());
+    }
+
+    #[test]
+    fn position_batches_are_capped_at_one_move_per_cell() {
+        let moves: Vec<CellMove> = (0..MAX_CELLS as u64 + 4).map(|cell_id| CellMove { cell_id, x: 0.0, y: 0.0 }).collect();
+        let applied = capped_batch(&moves);
+        assert_eq!(applied.len(), MAX_CELLS);
+        assert_eq!(applied.last().map(|m| m.cell_id), Some(MAX_CELLS as u64 - 1));
+        assert_eq!(capped_batch(&moves[..3]).len(), 3);
+    }
+
+    #[test]
+    fn disconnected_players_cannot_be_eaten() {
+        let now = Timestamp::from_micros_since_unix_epoch(5_000_000);
+        let ghost = Player { disconnected_at: Some(Timestamp::from_micros_since_unix_epoch(1)), ..test_player(1, 50.0) };
+        assert!(is_uneatable(&ghost, now));
+        assert!(!is_uneatable(&test_player(2, 50.0), now));
+    }
+
+    #[test]
+    fn ejected_mass_carries_the_ejectors_color() {
+        let cfg = default_config();
+        let player = Player { x: 1000.0, y: 1000.0, color: 0x33cc99, ..test_player(1, 500.0) };
+        let pellet = launch_pellet(&cfg, &player, EJECT_MASS_AMOUNT, (1.0, 0.0), Timestamp::from_micros_since_unix_epoch(0));
+        assert_eq!(pellet.color, 0x33cc99);
+        assert_eq!(pellet.ejector, player.identity);
+    }
+
+    #[test]
+    fn the_oldest_pellet_makes_room_at_the_cap() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let pellet = |id, micros| EjectedMass { id, ejected_at: at(micros), ..test_eject(1, 0.0, 0.0) };
+        let pellets = [pellet(4, 2_000), pellet(7, 1_000), pellet(3, 1_000), pellet(1, 3_000)];
+        assert_eq!(oldest_eject(pellets.into_iter()).map(|em| em.id), Some(3));
+        assert!(oldest_eject(std::iter::empty()).is_none());
+    }
+
+    #[test]
+    fn score_counts_food_and_kills_not_mass() {
+        let golden = FoodPellet { id: 1, x: 0.0, y: 0.0, radius: GOLDEN_FOOD_RADIUS, mass: GOLDEN_FOOD_MASS };
+        let plain = FoodPellet { mass: FOOD_MASS, radius: FOOD_RADIUS, ..golden };
+        assert_eq!(food_points(&plain), 1);
+        assert_eq!(food_points(&golden), GOLDEN_FOOD_MASS as u64);
+        assert_eq!(kill_points(0), KILL_SCORE);
+        assert_eq!(kill_points(250), 250 + KILL_SCORE);
+    }
+
+    #[test]
+    fn split_halves_launch_with_velocity_instead_of_teleporting() {
+        let cfg = default_config();
+        let owner = Identity::from_byte_array([1; 32]);
+        let half = split_half(&cfg, (owner, 0), (1000.0, 1000.0), 200.0, (0.0, 1.0), Timestamp::from_micros_since_unix_epoch(0));
+        assert!(close(half.x, 1000.0) && close(half.y, 1000.0 + half.radius));
+        assert!(half.vx == 0.0 && half.vy > 0.0);
+
+        // Coasting under friction carries it out to ~2.5 radii from the origin
+        let dt = MOTION_TICK_MICROS as f32 / 1_000_000.0;
+        let damping = (-EJECT_FRICTION * dt).exp();
+        let (mut y, mut vy) = (half.y, half.vy);
+        while vy != 0.0 {
+            y += vy * dt;
+            vy = damped_velocity((0.0, vy), damping, (0.0, 0.0), (0.0, 0.0)).1;
+        }
+        let travelled = (y - 1000.0) / half.radius;
+        assert!((2.0..3.0).contains(&travelled), "travelled {travelled} radii");
+    }
+
+    #[test]
+    fn clustered_food_gathers_around_the_hotspots() {
+        let cfg = GameConfig { food_distribution: FoodDistribution::Clustered, ..default_config() };
+        let clusters = [FoodCluster { id: 1, x: 800.0, y: 800.0 }, FoodCluster { id: 2, x: 2200.0, y: 2000.0 }];
+        let mut rng = StdRng::seed_from_u64(7);
+        let near = (0..500)
+            .map(|_| clustered_position(&cfg, &clusters, &mut rng))
+            .filter(|&(x, y)| clusters.iter().any(|c| within_reach(c.x, c.y, x, y, FOOD_CLUSTER_SPREAD * 3.0)))
+            .count();
+        assert!(near > 490, "only {near} of 500 pellets near a hotspot");
+
+        let (x, y) = clustered_position(&cfg, &[], &mut rng);
+        assert!((0.0..=cfg.width()).contains(&x) && (0.0..=cfg.height()).contains(&y));
+    }
+
+    #[test]
+    fn view_culling_keeps_centers_within_the_view_radius() {
+        let cfg = default_config();
+        let small = Player { x: 1000.0, y: 1000.0, ..test_player(1, 100.0) };
+        assert!(sees(&small, 1000.0 + BASE_VIEW_RADIUS, 1000.0));
+        assert!(!sees(&small, 1000.0 + BASE_VIEW_RADIUS + 1.0, 1000.0));
+
+        let big = Player { view_radius: view_radius(&cfg, 5000.0), ..small };
+        assert!(sees(&big, 1000.0 + BASE_VIEW_RADIUS + 1.0, 1000.0));
+    }
+
+    #[test]
+    fn own_cells_merge_on_contact_after_their_cooldown() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let a = PlayerCell { merge_at: at(1_000), ..test_cell(1, 0.0, 0.0, 100.0) };
+        let b = PlayerCell { merge_at: at(2_000), ..test_cell(1, a.radius * 2.0, 0.0, 100.0) };
+        assert!(!can_merge(&a, &b, at(1_999)));
+        assert!(can_merge(&a, &b, at(2_000)));
+
+        let apart = PlayerCell { x: a.radius * 2.0 + 1.0, ..test_cell(1, 0.0, 0.0, 100.0) };
+        assert!(!can_merge(&a, &apart, at(2_000)));
+        let foreign = test_cell(2, 0.0, 0.0, 100.0);
+        assert!(!can_merge(&a, &foreign, at(2_000)));
+    }
+
+    #[test]
+    fn achievements_unlock_at_their_thresholds() {
+        let stats = |players_eaten, food_eaten, max_mass_reached| PlayerStats {
+            identity: Identity::ZERO,
+            food_eaten,
+            players_eaten,
+            times_split: 0,
+            mass_ejected: 0.0,
+            max_mass_reached,
+            total_playtime_micros: 0,
+            deaths: 0,
+        };
+        assert!(stats(0, GLUTTON_FOOD_EATEN - 1, TITAN_MASS - 1.0).achievements().is_empty());
+        assert_eq!(stats(1, 0, 0.0).achievements(), vec![AchievementKind::FirstBlood]);
+        assert_eq!(
+            stats(0, GLUTTON_FOOD_EATEN, TITAN_MASS).achievements(),
+            vec![AchievementKind::Glutton, AchievementKind::Titan],
+        );
+    }
+
+    #[test]
+    fn eating_a_player_weighs_their_split_cells_too() {
+        let cfg = default_config();
+        let now = Timestamp::from_micros_since_unix_epoch(0);
+        let eater = test_player(1, 500.0);
+        let target = Player { x: 5.0, ..test_player(2, 200.0) };
+        assert!(can_eat_player(&cfg, &eater, &target, 200.0, now));
+        assert!(!can_eat_player(&cfg, &eater, &target, 480.0, now));
+    }
+
+    #[test]
+    fn split_mass_is_tallied_per_owner() {
+        let cells = [test_cell(1, 0.0, 0.0, 100.0), test_cell(2, 0.0, 0.0, 60.0), test_cell(1, 0.0, 0.0, 50.0)];
+        let by_owner = mass_by_owner(cells.into_iter());
+        assert_eq!(by_owner.len(), 2);
+        assert!(close(by_owner[&Identity::from_byte_array([1; 32])], 150.0));
+        assert!(close(by_owner[&Identity::from_byte_array([2; 32])], 60.0));
+        assert!(!by_owner.contains_key(&Identity::from_byte_array([3; 32])));
+    }
+
+    #[test]
+    fn growing_at_the_edge_pushes_the_cell_inward() {
+        let cfg = default_config();
+        let small = cfg.mass_to_radius(100.0);
+        let (x, y, mass, radius) = grown(&cfg, &[], (cfg.width() - small, cfg.height() / 2.0), 4000.0);
+        assert!(close(mass, 4000.0));
+        assert!(x + radius <= cfg.width() + 1e-3 && x < cfg.width() - small);
+        assert!(close(y, cfg.height() / 2.0));
+
+        let (_, _, capped, _) = grown(&cfg, &[], (1500.0, 1500.0), cfg.max_mass * 2.0);
+        assert!(close(capped, cfg.max_mass));
+    }
+
+    #[test]
+    fn ejects_follow_the_cursor_and_fan_out_within_the_spread() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let exact = GameConfig { eject_spread: 0.0, ..default_config() };
+        let (nx, ny) = eject_direction(&exact, (3.0, 4.0), &mut rng).unwrap();
+        assert!(close(nx, 0.6) && close(ny, 0.8));
+        assert!(eject_direction(&exact, (0.0, 0.0), &mut rng).is_none());
+
+        let cfg = default_config();
+        let first = eject_direction(&cfg, (1.0, 0.0), &mut rng).unwrap();
+        let second = eject_direction(&cfg, (1.0, 0.0), &mut rng).unwrap();
+        assert_ne!(first, second);
+        for (nx, ny) in [first, second] {
+            assert!(ny.atan2(nx).abs() <= cfg.eject_spread + 1e-6);
+        }
+    }
+
+    #[test]
+    fn a_drifting_pellet_that_reaches_a_virus_feeds_it_once() {
+        let cfg = default_config();
+        let virus = Virus { id: 1, x: 1000.0, y: 1000.0, radius: 30.0, mass: VIRUS_MASS, feed_count: 0 };
+        let mut em = EjectedMass { x: 900.0, y: 1000.0, vx: 2000.0, vy: 0.0, ..test_eject(1, 900.0, 1000.0) };
+        assert!(!touches_virus(&virus, (em.x, em.y), em.radius));
+        let mut ticks = 0;
+        while !touches_virus(&virus, (em.x, em.y), em.radius) && ticks < 100 {
+            let ((x, y), (vx, vy)) = drift_step(&cfg, &em);
+            em = EjectedMass { x, y, vx, vy, ..em };
+            ticks += 1;
+        }
+        assert!(touches_virus(&virus, (em.x, em.y), em.radius));
+
+        let fed = fed_virus(&cfg, virus, em.mass);
+        assert_eq!(fed.feed_count, 1);
+        assert!(close(fed.mass, VIRUS_MASS + em.mass));
+        assert!(fed.radius > cfg.mass_to_radius(VIRUS_MASS));
+    }
+
+    #[test]
+    fn spawning_past_the_cap_is_refused_until_a_player_leaves() {
+        let cfg = GameConfig { max_players: 2, ..default_config() };
+        let newcomer = Identity::from_byte_array([9; 32]);
+        assert!(is_full(&cfg, [test_player(1, 20.0), test_player(2, 20.0)], newcomer));
+
+        let gone = Player {
+            disconnected_at: Some(Timestamp::from_micros_since_unix_epoch(0)),
+            ..test_player(2, 20.0)
+        };
+        assert!(!is_full(&cfg, [test_player(1, 20.0), gone], newcomer));
+        assert!(!is_full(&cfg, [test_player(1, 20.0)], newcomer));
+        // A player already counted doesn't block their own respawn
+        assert!(!is_full(&cfg, [test_player(1, 20.0), test_player(9, 20.0)], newcomer));
+    }
+
+    #[test]
+    fn own_fresh_pellets_cannot_be_eaten_back_until_the_delay_passes() {
+        let em = test_eject(1, 100.0, 100.0);
+        let (owner, other) = (Identity::from_byte_array([1; 32]), Identity::from_byte_array([2; 32]));
+        let cell = (100.0, 100.0, 30.0);
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+
+        assert!(!can_eat_eject(owner, cell, &em, at(0)));
+        assert!(!can_eat_eject(owner, cell, &em, at(SELF_EAT_DELAY_MICROS - 1)));
+        assert!(can_eat_eject(owner, cell, &em, at(SELF_EAT_DELAY_MICROS)));
+        assert!(can_eat_eject(other, cell, &em, at(0)));
+    }
+
+    #[test]
+    fn event_payloads_are_json_and_the_trimmer_drops_the_oldest() {
+        assert_eq!(spawn_payload("A \"b\"", 0xff0000), "{\"name\":\"A \\\"b\\\"\",\"color\":16711680}");
+        assert_eq!(mass_payload(16.0), "{\"mass\":16}");
+        assert_eq!(mass_payload(12.5), "{\"mass\":12.5}");
+
+        assert_eq!(oldest_beyond(vec![5, 1, 4, 2, 3], 3), vec![1, 2]);
+        assert!(oldest_beyond(vec![2, 1], 3).is_empty());
+        assert!(oldest_beyond(vec![1, 2, 3], 3).is_empty());
+    }
+
+    #[test]
+    fn the_same_seed_lays_out_the_same_food() {
+        let cfg = default_config();
+        let layout = |seed| -> Vec<(f32, f32, f32, f32)> {
+            (0..50).map(|draw| roll_food(&cfg, &[], &mut seeded_rng(seed, draw))).collect()
+        };
+        assert_eq!(layout(42), layout(42));
+        assert_ne!(layout(42), layout(43));
+
+        let pellets = layout(42);
+        assert_ne!(pellets[0], pellets[1]);
+    }
+
+    #[test]
+    fn the_audit_totals_every_mass_and_tracks_the_change() {
+        let now = Timestamp::from_micros_since_unix_epoch(0);
+        let first = mass_audit([100.0, 40.0, 30.0, 16.0], None, now);
+        assert!(close(first.total, 186.0));
+        assert!(close(first.delta, 0.0));
+
+        // Eating food and a pellet only moves mass between buckets
+        let after_eats = mass_audit([100.0 + 16.0 + 2.0, 40.0, 28.0, 0.0], Some(&first), now);
+        assert!(close(after_eats.total, 186.0));
+        assert!(close(after_eats.delta, 0.0));
+
+        let duped = mass_audit([150.0, 40.0, 28.0, 0.0], Some(&after_eats), now);
+        assert!(close(duped.delta, 32.0));
+    }
+
+    #[test]
+    fn a_player_at_the_cell_cap_cannot_split_however_heavy() {
+        let cfg = default_config();
+        assert!(cells_to_split(&cfg, &[100_000.0; MAX_CELLS]).is_empty());
+        assert!(cells_to_split(&cfg, &[100_000.0; MAX_CELLS + 1]).is_empty());
+        assert_eq!(cells_to_split(&cfg, &[100_000.0; MAX_CELLS - 1]), vec![0]);
+    }
+
+    #[test]
+    fn small_players_pull_nearby_food_and_big_ones_do_not() {
+        let small = Player { radius: 20.0, ..test_player(1, 20.0) };
+        let food = FoodPellet { id: 1, x: 50.0, y: 0.0, radius: FOOD_RADIUS, mass: FOOD_MASS };
+        let dist = magnet_pull(&small, small.mass, &food).unwrap();
+        assert!(close(dist, 50.0));
+        let (x, y) = capped_move(food.x, food.y, small.x, small.y, FOOD_MAGNET_STEP);
+        assert!(close(x, 50.0 - FOOD_MAGNET_STEP) && close(y, 0.0));
+
+        let far = FoodPellet { x: 20.0 + FOOD_MAGNET_RANGE + 1.0, ..food };
+        assert!(magnet_pull(&small, small.mass, &far).is_none());
+
+        let big = Player { radius: 20.0, ..test_player(2, FOOD_MAGNET_MASS) };
+        assert!(magnet_pull(&big, big.mass, &food).is_none());
+        // Split cells count toward the threshold too
+        assert!(magnet_pull(&small, FOOD_MAGNET_MASS, &food).is_none());
+    }
+
+    #[test]
+    fn hardcore_victims_stay_dead_until_the_graveyard_is_cleared() {
+        let now = Timestamp::from_micros_since_unix_epoch(5);
+        let victim = Player { name: "victim".to_string(), ..test_player(1, 50.0) };
+        let hardcore = GameConfig { hardcore: true, ..default_config() };
+
+        let grave = grave_for(&hardcore, &victim, false, now).unwrap();
+        assert_eq!(grave.identity, victim.identity);
+        assert_eq!(grave.name, "victim");
+        assert!(grave_for(&hardcore, &victim, true, now).is_none());
+        assert!(grave_for(&GameConfig { hardcore: false, ..default_config() }, &victim, false, now).is_none());
+
+        assert!(spawn_allowed(false, true).is_err());
+        // A round reset empties the graveyard
+        assert!(spawn_allowed(false, false).is_ok());
+        assert!(spawn_allowed(true, false).is_err());
+    }
+
+    #[test]
+    fn ejecting_pushes_the_cell_back_briefly_and_caps_the_speed() {
+        let (vx, vy) = recoil((0.0, 0.0), (1.0, 0.0));
+        assert!(close(vx, -EJECT_IMPULSE) && vy == 0.0);
+
+        // The shift against the eject direction coasts out over a few ticks
+        let dt = MOTION_TICK_MICROS as f32 / 1_000_000.0;
+        let damping = (-EJECT_FRICTION * dt).exp();
+        let (mut x, mut v, mut ticks) = (0.0, (vx, vy), 0);
+        while v != (0.0, 0.0) {
+            x += v.0 * dt;
+            v = damped_velocity(v, damping, (0.0, 0.0), (0.0, 0.0));
+            ticks += 1;
+        }
+        assert!(x < 0.0 && ticks < 100);
+
+        // Burst ejects never accumulate past the cap
+        let mut v = (0.0, 0.0);
+        for _ in 0..20 {
+            v = recoil(v, (1.0, 0.0));
+        }
+        assert!(close((v.0 * v.0 + v.1 * v.1).sqrt(), MAX_EJECT_IMPULSE));
+    }
+
+    #[test]
+    fn metrics_count_every_table_after_setup() {
+        let cfg = default_config();
+        let players = [test_player(1, cfg.initial_mass), test_player(2, 80.0)];
+        let cells = [test_cell(2, 0.0, 0.0, 40.0), test_cell(2, 10.0, 0.0, 40.0)];
+        let ejected = [test_eject(1, 0.0, 0.0)];
+        let player_mass: Vec<f32> = players.iter().map(|p| p.mass).collect();
+        let cell_mass: Vec<f32> = cells.iter().map(|c| c.mass).collect();
+
+        let metrics = build_snapshot(
+            Timestamp::from_micros_since_unix_epoch(0),
+            &player_mass,
+            &cell_mass,
+            cfg.max_food as u64,
+            ejected.len() as u64,
+            3,
+        );
+        assert_eq!(metrics.id, 0);
+        assert_eq!(metrics.player_count, 2);
+        assert_eq!(metrics.cell_count, 2);
+        assert_eq!(metrics.food_count, cfg.max_food);
+        assert_eq!(metrics.ejected_count, 1);
+        assert_eq!(metrics.virus_count, 3);
+        assert!(close(metrics.total_mass, cfg.initial_mass + 160.0));
+    }
+
+    #[test]
+    fn split_cells_take_their_owners_color() {
+        let cfg = default_config();
+        let owner = Player { color: 0x33cc99, ..test_player(1, 400.0) };
+        let half = split_half(
+            &cfg,
+            (owner.identity, owner.color),
+            (owner.x, owner.y),
+            owner.mass / 2.0,
+            (1.0, 0.0),
+            Timestamp::from_micros_since_unix_epoch(0),
+        );
+        assert_eq!(half.player_identity, owner.identity);
+        assert_eq!(half.color, 0x33cc99);
+    }
+
+    #[test]
+    fn the_invulnerable_flag_lasts_until_protection_lapses_or_the_first_eat() {
+        let at = |micros| Timestamp::from_micros_since_unix_epoch(micros);
+        let spawned = Player {
+            invulnerable: true,
+            spawn_protected_until: at(SPAWN_PROTECTION_MICROS),
+            ..test_player(1, 20.0)
+        };
+        assert!(is_uneatable(&spawned, at(0)));
+        assert!(!protection_lapsed(&spawned, at(SPAWN_PROTECTION_MICROS - 1)));
+        assert!(protection_lapsed(&spawned, at(SPAWN_PROTECTION_MICROS)));
+
+        let ate = unprotected(spawned, at(1_000));
+        assert!(!ate.invulnerable);
+        assert!(!is_spawn_protected(&ate, at(1_000)));
+        assert!(!protection_lapsed(&ate, at(SPAWN_PROTECTION_MICROS)));
+    }
+
+    #[test]
+    fn editing_the_palette_changes_the_colors_players_get() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut palette = vec![0xff0000];
+        assert_ne!(spawn_color(0x00ff00, None, &palette, &mut rng), 0x00ff00);
+
+        assert!(can_add_color(&palette, 0x00ff00));
+        palette.push(0x00ff00);
+        assert_eq!(spawn_color(0x00ff00, None, &palette, &mut rng), 0x00ff00);
+        assert!(!can_add_color(&palette, 0x00ff00));
+        assert!(!can_add_color(&palette, 0x1000000));
+
+        assert!(can_remove_color(&palette, 0xff0000));
+        palette.retain(|&c| c != 0xff0000);
+        assert_eq!(spawn_color(0xff0000, None, &palette, &mut rng), 0x00ff00);
+        // The last color stays
+        assert!(!can_remove_color(&palette, 0x00ff00));
+        assert!(!can_remove_color(&[0xff0000, 0x00ff00], 0x0000ff));
+    }
+
+    #[test]
+    fn merging_a_distant_cell_moves_the_main_cell_to_the_centroid() {
+        let main = Player { x: 1000.0, y: 1000.0, ..test_player(1, 300.0) };
+        let far = test_cell(1, 1400.0, 1000.0, 100.0);
+        let ((x, y), mass) = merged_centroid(&main, &[far]);
+        assert!(close(mass, 400.0));
+        assert!(close(x, 1100.0) && close(y, 1000.0));
+
+        let ((x, y), mass) = merged_centroid(&main, &[]);
+        assert!(close(mass, 300.0) && close(x, 1000.0) && close(y, 1000.0));
+    }
+
+    #[test]
+    fn a_reset_arena_is_laid_out_like_a_fresh_one() {
+        let cfg = default_config();
+        let fresh = arena_layout(&cfg, &mut StdRng::seed_from_u64(11));
+        let reset = arena_layout(&cfg, &mut StdRng::seed_from_u64(12));
+        for (portals, walls) in [&fresh, &reset] {
+            assert_eq!(portals.len(), PORTAL_COUNT as usize);
+            assert_eq!(walls.len(), OBSTACLE_COUNT as usize);
+            for wall in walls {
+                assert!((OBSTACLE_MIN_SIDE..OBSTACLE_MAX_SIDE).contains(&wall.width));
+                assert!(wall.x >= 0.0 && wall.x <= cfg.width() && wall.y >= 0.0 && wall.y <= cfg.height());
+            }
+        }
+        let again = arena_layout(&cfg, &mut StdRng::seed_from_u64(11));
+        assert_eq!(fresh.0[0].entry_x, again.0[0].entry_x);
+        assert_eq!(fresh.1[0].x, again.1[0].x);
     }
 }
@@ -16,4 +16,9 @@ export default __t.row({
   y: __t.f32(),
   radius: __t.f32(),
   mass: __t.f32(),
+  ejectedAt: __t.timestamp().name("ejected_at"),
+  vx: __t.f32(),
+  vy: __t.f32(),
+  color: __t.u32(),
+  ejector: __t.identity(),
 });
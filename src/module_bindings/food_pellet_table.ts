@@ -15,4 +15,5 @@ export default __t.row({
   x: __t.f32(),
   y: __t.f32(),
   radius: __t.f32(),
+  mass: __t.f32(),
 });
@@ -9,10 +9,54 @@ import {
   type AlgebraicTypeType as __AlgebraicTypeType,
   type Infer as __Infer,
 } from "spacetimedb";
+import {
+  EatOverlapMode,
+  FoodDistribution,
+  FoodReplacement,
+  WorldShape,
+} from "./types";
 
 export default __t.row({
   id: __t.u32().primaryKey(),
   maxFood: __t.u32().name("max_food"),
   worldWidth: __t.u32().name("world_width"),
   worldHeight: __t.u32().name("world_height"),
+  maxMass: __t.f32().name("max_mass"),
+  botCount: __t.u32().name("bot_count"),
+  maxPlayers: __t.u32().name("max_players"),
+  get worldShape() {
+    return WorldShape.name("world_shape");
+  },
+  decayMassExponent: __t.f32().name("decay_mass_exponent"),
+  decayIntervalMicros: __t.i64().name("decay_interval_micros"),
+  decayRate: __t.f32().name("decay_rate"),
+  ejectDistance: __t.f32().name("eject_distance"),
+  ejectSpread: __t.f32().name("eject_spread"),
+  autoResize: __t.bool().name("auto_resize"),
+  idleTimeoutMicros: __t.i64().name("idle_timeout_micros"),
+  hardcore: __t.bool(),
+  foodMagnet: __t.bool().name("food_magnet"),
+  paused: __t.bool(),
+  get foodDistribution() {
+    return FoodDistribution.name("food_distribution");
+  },
+  get foodReplacement() {
+    return FoodReplacement.name("food_replacement");
+  },
+  initialMass: __t.f32().name("initial_mass"),
+  minSplitMass: __t.f32().name("min_split_mass"),
+  mergeDelayMicros: __t.i64().name("merge_delay_micros"),
+  mergeDelayPerMassMicros: __t.f32().name("merge_delay_per_mass_micros"),
+  minCellMass: __t.f32().name("min_cell_mass"),
+  ejectMassAmount: __t.f32().name("eject_mass_amount"),
+  radiusScale: __t.f32().name("radius_scale"),
+  radiusExponent: __t.f32().name("radius_exponent"),
+  foodRadius: __t.f32().name("food_radius"),
+  absorbRatio: __t.f32().name("absorb_ratio"),
+  scatterLostMass: __t.bool().name("scatter_lost_mass"),
+  get eatOverlapMode() {
+    return EatOverlapMode.name("eat_overlap_mode");
+  },
+  eatCoverFactor: __t.f32().name("eat_cover_factor"),
+  rngSeed: __t.option(__t.u64()).name("rng_seed"),
 });
@@ -34,31 +34,157 @@ import {
 } from "spacetimedb";
 
 // Import all reducer arg schemas
+import AddObstacleReducer from "./add_obstacle_reducer";
+import AddPaletteColorReducer from "./add_palette_color_reducer";
+import AddPortalReducer from "./add_portal_reducer";
+import AttemptMergeReducer from "./attempt_merge_reducer";
+import BanPlayerReducer from "./ban_player_reducer";
+import BecomeSpectatorReducer from "./become_spectator_reducer";
+import ClearSafeZoneReducer from "./clear_safe_zone_reducer";
 import DespawnPlayerReducer from "./despawn_player_reducer";
+import EatCellReducer from "./eat_cell_reducer";
 import EatEjectedMassReducer from "./eat_ejected_mass_reducer";
+import EatEjectedMassCellReducer from "./eat_ejected_mass_cell_reducer";
 import EatFoodReducer from "./eat_food_reducer";
 import EatFoodCellReducer from "./eat_food_cell_reducer";
+import EatNearbyFoodReducer from "./eat_nearby_food_reducer";
 import EatPlayerReducer from "./eat_player_reducer";
 import EatPlayerCellReducer from "./eat_player_cell_reducer";
+import EatPowerupReducer from "./eat_powerup_reducer";
+import EatVirusReducer from "./eat_virus_reducer";
 import EjectMassReducer from "./eject_mass_reducer";
+import ExportStateReducer from "./export_state_reducer";
+import FeedVirusReducer from "./feed_virus_reducer";
+import FollowPlayerReducer from "./follow_player_reducer";
+import KickPlayerReducer from "./kick_player_reducer";
+import LeaveSpectatorReducer from "./leave_spectator_reducer";
+import PauseGameReducer from "./pause_game_reducer";
+import RefreshThreatsReducer from "./refresh_threats_reducer";
+import RefreshViewReducer from "./refresh_view_reducer";
+import RemoveObstacleReducer from "./remove_obstacle_reducer";
+import RemovePaletteColorReducer from "./remove_palette_color_reducer";
+import RemovePortalReducer from "./remove_portal_reducer";
+import ResetArenaReducer from "./reset_arena_reducer";
+import ResetStatsReducer from "./reset_stats_reducer";
+import RespawnReducer from "./respawn_reducer";
+import ResumeGameReducer from "./resume_game_reducer";
+import SendChatReducer from "./send_chat_reducer";
+import SetAbsorbRuleReducer from "./set_absorb_rule_reducer";
+import SetAutoResizeReducer from "./set_auto_resize_reducer";
+import SetBotCountReducer from "./set_bot_count_reducer";
+import SetConfigReducer from "./set_config_reducer";
+import SetDecayReducer from "./set_decay_reducer";
+import SetDecayExponentReducer from "./set_decay_exponent_reducer";
+import SetEatOverlapReducer from "./set_eat_overlap_reducer";
+import SetEjectDistanceReducer from "./set_eject_distance_reducer";
+import SetEjectSpreadReducer from "./set_eject_spread_reducer";
+import SetFoodDistributionReducer from "./set_food_distribution_reducer";
+import SetFoodMagnetReducer from "./set_food_magnet_reducer";
+import SetFoodReplacementReducer from "./set_food_replacement_reducer";
+import SetHardcoreReducer from "./set_hardcore_reducer";
+import SetIdleTimeoutReducer from "./set_idle_timeout_reducer";
+import SetMassRulesReducer from "./set_mass_rules_reducer";
+import SetMaxMassReducer from "./set_max_mass_reducer";
+import SetMaxPlayersReducer from "./set_max_players_reducer";
+import SetMergeDelayReducer from "./set_merge_delay_reducer";
+import SetMinCellMassReducer from "./set_min_cell_mass_reducer";
+import SetRngSeedReducer from "./set_rng_seed_reducer";
+import SetSizeCurveReducer from "./set_size_curve_reducer";
+import SetWorldShapeReducer from "./set_world_shape_reducer";
 import SpawnPlayerReducer from "./spawn_player_reducer";
+import SpawnPlayerTeamReducer from "./spawn_player_team_reducer";
 import SplitCellReducer from "./split_cell_reducer";
+import StartRoundReducer from "./start_round_reducer";
+import StartSafeZoneReducer from "./start_safe_zone_reducer";
+import TransferMassReducer from "./transfer_mass_reducer";
+import UnbanPlayerReducer from "./unban_player_reducer";
+import UpdateAllPositionsReducer from "./update_all_positions_reducer";
 import UpdateCellPositionReducer from "./update_cell_position_reducer";
 import UpdatePositionReducer from "./update_position_reducer";
 
 // Import all procedure arg schemas
 
 // Import all table schema definitions
+import AchievementRow from "./achievement_table";
+import BotRow from "./bot_table";
+import ChatMessageRow from "./chat_message_table";
+import ColorPaletteRow from "./color_palette_table";
 import EjectedMassRow from "./ejected_mass_table";
+import FoodClusterRow from "./food_cluster_table";
 import FoodPelletRow from "./food_pellet_table";
 import GameConfigRow from "./game_config_table";
+import GameEventRow from "./game_event_table";
+import GameRoundRow from "./game_round_table";
+import GameSnapshotRow from "./game_snapshot_table";
+import GraveyardRow from "./graveyard_table";
+import KillEventRow from "./kill_event_table";
+import LeaderboardRow from "./leaderboard_table";
+import MassAuditRow from "./mass_audit_table";
+import MinimapBlipRow from "./minimap_blip_table";
+import ObstacleRow from "./obstacle_table";
 import PlayerRow from "./player_table";
 import PlayerCellRow from "./player_cell_table";
+import PlayerStatsRow from "./player_stats_table";
+import PortalRow from "./portal_table";
+import PowerUpRow from "./power_up_table";
+import SafeZoneRow from "./safe_zone_table";
+import SpectatorRow from "./spectator_table";
+import TeamScoreRow from "./team_score_table";
+import ThreatRow from "./threat_table";
+import ViewEntityRow from "./view_entity_table";
+import VirusRow from "./virus_table";
 
 /** Type-only namespace exports for generated type groups. */
 
 /** The schema information for all tables in this module. This is defined the same was as the tables would have been defined in the server. */
 const tablesSchema = __schema({
+  achievement: __table({
+    name: 'achievement',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'achievement_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, AchievementRow),
+  bot: __table({
+    name: 'bot',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'bot_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, BotRow),
+  chat_message: __table({
+    name: 'chat_message',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'chat_message_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, ChatMessageRow),
+  color_palette: __table({
+    name: 'color_palette',
+    indexes: [
+      { name: 'color', algorithm: 'btree', columns: [
+        'color',
+      ] },
+    ],
+    constraints: [
+      { name: 'color_palette_color_key', constraint: 'unique', columns: ['color'] },
+    ],
+  }, ColorPaletteRow),
   ejected_mass: __table({
     name: 'ejected_mass',
     indexes: [
@@ -70,6 +196,17 @@ const tablesSchema = __schema({
       { name: 'ejected_mass_id_key', constraint: 'unique', columns: ['id'] },
     ],
   }, EjectedMassRow),
+  food_cluster: __table({
+    name: 'food_cluster',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'food_cluster_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, FoodClusterRow),
   food_pellet: __table({
     name: 'food_pellet',
     indexes: [
@@ -92,6 +229,105 @@ const tablesSchema = __schema({
       { name: 'game_config_id_key', constraint: 'unique', columns: ['id'] },
     ],
   }, GameConfigRow),
+  game_event: __table({
+    name: 'game_event',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'game_event_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, GameEventRow),
+  game_round: __table({
+    name: 'game_round',
+    indexes: [
+      { name: 'round_id', algorithm: 'btree', columns: [
+        'roundId',
+      ] },
+    ],
+    constraints: [
+      { name: 'game_round_round_id_key', constraint: 'unique', columns: ['roundId'] },
+    ],
+  }, GameRoundRow),
+  game_snapshot: __table({
+    name: 'game_snapshot',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'game_snapshot_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, GameSnapshotRow),
+  graveyard: __table({
+    name: 'graveyard',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'graveyard_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, GraveyardRow),
+  kill_event: __table({
+    name: 'kill_event',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'kill_event_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, KillEventRow),
+  leaderboard: __table({
+    name: 'leaderboard',
+    indexes: [
+      { name: 'rank', algorithm: 'btree', columns: [
+        'rank',
+      ] },
+    ],
+    constraints: [
+      { name: 'leaderboard_rank_key', constraint: 'unique', columns: ['rank'] },
+    ],
+  }, LeaderboardRow),
+  mass_audit: __table({
+    name: 'mass_audit',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'mass_audit_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, MassAuditRow),
+  minimap_blip: __table({
+    name: 'minimap_blip',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'minimap_blip_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, MinimapBlipRow),
+  obstacle: __table({
+    name: 'obstacle',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'obstacle_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, ObstacleRow),
   player: __table({
     name: 'player',
     indexes: [
@@ -109,24 +345,188 @@ const tablesSchema = __schema({
       { name: 'cell_id', algorithm: 'btree', columns: [
         'cellId',
       ] },
+      { name: 'player_identity', algorithm: 'btree', columns: [
+        'playerIdentity',
+      ] },
     ],
     constraints: [
       { name: 'player_cell_cell_id_key', constraint: 'unique', columns: ['cellId'] },
     ],
   }, PlayerCellRow),
+  player_stats: __table({
+    name: 'player_stats',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'player_stats_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, PlayerStatsRow),
+  portal: __table({
+    name: 'portal',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'portal_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, PortalRow),
+  power_up: __table({
+    name: 'power_up',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'power_up_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, PowerUpRow),
+  safe_zone: __table({
+    name: 'safe_zone',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'safe_zone_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, SafeZoneRow),
+  spectator: __table({
+    name: 'spectator',
+    indexes: [
+      { name: 'identity', algorithm: 'btree', columns: [
+        'identity',
+      ] },
+    ],
+    constraints: [
+      { name: 'spectator_identity_key', constraint: 'unique', columns: ['identity'] },
+    ],
+  }, SpectatorRow),
+  team_score: __table({
+    name: 'team_score',
+    indexes: [
+      { name: 'team_id', algorithm: 'btree', columns: [
+        'teamId',
+      ] },
+    ],
+    constraints: [
+      { name: 'team_score_team_id_key', constraint: 'unique', columns: ['teamId'] },
+    ],
+  }, TeamScoreRow),
+  threat: __table({
+    name: 'threat',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'viewer', algorithm: 'btree', columns: [
+        'viewer',
+      ] },
+    ],
+    constraints: [
+      { name: 'threat_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, ThreatRow),
+  view_entity: __table({
+    name: 'view_entity',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+      { name: 'viewer', algorithm: 'btree', columns: [
+        'viewer',
+      ] },
+    ],
+    constraints: [
+      { name: 'view_entity_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, ViewEntityRow),
+  virus: __table({
+    name: 'virus',
+    indexes: [
+      { name: 'id', algorithm: 'btree', columns: [
+        'id',
+      ] },
+    ],
+    constraints: [
+      { name: 'virus_id_key', constraint: 'unique', columns: ['id'] },
+    ],
+  }, VirusRow),
 });
 
 /** The schema information for all reducers in this module. This is defined the same way as the reducers would have been defined in the server, except the body of the reducer is omitted in code generation. */
 const reducersSchema = __reducers(
+  __reducerSchema("add_obstacle", AddObstacleReducer),
+  __reducerSchema("add_palette_color", AddPaletteColorReducer),
+  __reducerSchema("add_portal", AddPortalReducer),
+  __reducerSchema("attempt_merge", AttemptMergeReducer),
+  __reducerSchema("ban_player", BanPlayerReducer),
+  __reducerSchema("become_spectator", BecomeSpectatorReducer),
+  __reducerSchema("clear_safe_zone", ClearSafeZoneReducer),
   __reducerSchema("despawn_player", DespawnPlayerReducer),
+  __reducerSchema("eat_cell", EatCellReducer),
   __reducerSchema("eat_ejected_mass", EatEjectedMassReducer),
+  __reducerSchema("eat_ejected_mass_cell", EatEjectedMassCellReducer),
   __reducerSchema("eat_food", EatFoodReducer),
   __reducerSchema("eat_food_cell", EatFoodCellReducer),
+  __reducerSchema("eat_nearby_food", EatNearbyFoodReducer),
   __reducerSchema("eat_player", EatPlayerReducer),
   __reducerSchema("eat_player_cell", EatPlayerCellReducer),
+  __reducerSchema("eat_powerup", EatPowerupReducer),
+  __reducerSchema("eat_virus", EatVirusReducer),
   __reducerSchema("eject_mass", EjectMassReducer),
+  __reducerSchema("export_state", ExportStateReducer),
+  __reducerSchema("feed_virus", FeedVirusReducer),
+  __reducerSchema("follow_player", FollowPlayerReducer),
+  __reducerSchema("kick_player", KickPlayerReducer),
+  __reducerSchema("leave_spectator", LeaveSpectatorReducer),
+  __reducerSchema("pause_game", PauseGameReducer),
+  __reducerSchema("refresh_threats", RefreshThreatsReducer),
+  __reducerSchema("refresh_view", RefreshViewReducer),
+  __reducerSchema("remove_obstacle", RemoveObstacleReducer),
+  __reducerSchema("remove_palette_color", RemovePaletteColorReducer),
+  __reducerSchema("remove_portal", RemovePortalReducer),
+  __reducerSchema("reset_arena", ResetArenaReducer),
+  __reducerSchema("reset_stats", ResetStatsReducer),
+  __reducerSchema("respawn", RespawnReducer),
+  __reducerSchema("resume_game", ResumeGameReducer),
+  __reducerSchema("send_chat", SendChatReducer),
+  __reducerSchema("set_absorb_rule", SetAbsorbRuleReducer),
+  __reducerSchema("set_auto_resize", SetAutoResizeReducer),
+  __reducerSchema("set_bot_count", SetBotCountReducer),
+  __reducerSchema("set_config", SetConfigReducer),
+  __reducerSchema("set_decay", SetDecayReducer),
+  __reducerSchema("set_decay_exponent", SetDecayExponentReducer),
+  __reducerSchema("set_eat_overlap", SetEatOverlapReducer),
+  __reducerSchema("set_eject_distance", SetEjectDistanceReducer),
+  __reducerSchema("set_eject_spread", SetEjectSpreadReducer),
+  __reducerSchema("set_food_distribution", SetFoodDistributionReducer),
+  __reducerSchema("set_food_magnet", SetFoodMagnetReducer),
+  __reducerSchema("set_food_replacement", SetFoodReplacementReducer),
+  __reducerSchema("set_hardcore", SetHardcoreReducer),
+  __reducerSchema("set_idle_timeout", SetIdleTimeoutReducer),
+  __reducerSchema("set_mass_rules", SetMassRulesReducer),
+  __reducerSchema("set_max_mass", SetMaxMassReducer),
+  __reducerSchema("set_max_players", SetMaxPlayersReducer),
+  __reducerSchema("set_merge_delay", SetMergeDelayReducer),
+  __reducerSchema("set_min_cell_mass", SetMinCellMassReducer),
+  __reducerSchema("set_rng_seed", SetRngSeedReducer),
+  __reducerSchema("set_size_curve", SetSizeCurveReducer),
+  __reducerSchema("set_world_shape", SetWorldShapeReducer),
   __reducerSchema("spawn_player", SpawnPlayerReducer),
+  __reducerSchema("spawn_player_team", SpawnPlayerTeamReducer),
   __reducerSchema("split_cell", SplitCellReducer),
+  __reducerSchema("start_round", StartRoundReducer),
+  __reducerSchema("start_safe_zone", StartSafeZoneReducer),
+  __reducerSchema("transfer_mass", TransferMassReducer),
+  __reducerSchema("unban_player", UnbanPlayerReducer),
+  __reducerSchema("update_all_positions", UpdateAllPositionsReducer),
   __reducerSchema("update_cell_position", UpdateCellPositionReducer),
   __reducerSchema("update_position", UpdatePositionReducer),
 );
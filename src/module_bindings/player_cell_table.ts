@@ -17,4 +17,9 @@ export default __t.row({
   y: __t.f32(),
   radius: __t.f32(),
   mass: __t.f32(),
+  lastMoveMicros: __t.i64().name("last_move_micros"),
+  mergeAt: __t.timestamp().name("merge_at"),
+  vx: __t.f32(),
+  vy: __t.f32(),
+  color: __t.u32(),
 });
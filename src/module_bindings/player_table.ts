@@ -18,4 +18,19 @@ export default __t.row({
   radius: __t.f32(),
   mass: __t.f32(),
   color: __t.u32(),
+  lastMoveMicros: __t.i64().name("last_move_micros"),
+  spawnedAt: __t.timestamp().name("spawned_at"),
+  spawnProtectedUntil: __t.timestamp().name("spawn_protected_until"),
+  invulnerable: __t.bool(),
+  teamId: __t.option(__t.u32()).name("team_id"),
+  disconnectedAt: __t.option(__t.timestamp()).name("disconnected_at"),
+  onlineSince: __t.timestamp().name("online_since"),
+  lastActiveAt: __t.timestamp().name("last_active_at"),
+  boostUntil: __t.timestamp().name("boost_until"),
+  shieldUntil: __t.timestamp().name("shield_until"),
+  teleportReadyAt: __t.timestamp().name("teleport_ready_at"),
+  score: __t.u64(),
+  viewRadius: __t.f32().name("view_radius"),
+  vx: __t.f32(),
+  vy: __t.f32(),
 });
@@ -10,37 +10,367 @@ import {
   type Infer as __Infer,
 } from "spacetimedb";
 
+export const Achievement = __t.object("Achievement", {
+  id: __t.u64(),
+  identity: __t.identity(),
+  get kind() {
+    return AchievementKind;
+  },
+  unlockedAt: __t.timestamp(),
+});
+export type Achievement = __Infer<typeof Achievement>;
+
+export const AchievementKind = __t.enum("AchievementKind", {
+  FirstBlood: __t.unit(),
+  Glutton: __t.unit(),
+  Titan: __t.unit(),
+});
+export type AchievementKind = __Infer<typeof AchievementKind>;
+
+export const Admin = __t.object("Admin", {
+  identity: __t.identity(),
+});
+export type Admin = __Infer<typeof Admin>;
+
+export const BanList = __t.object("BanList", {
+  identity: __t.identity(),
+  bannedAt: __t.timestamp(),
+});
+export type BanList = __Infer<typeof BanList>;
+
+export const Bot = __t.object("Bot", {
+  identity: __t.identity(),
+  index: __t.u32(),
+});
+export type Bot = __Infer<typeof Bot>;
+
+export const BotTickSchedule = __t.object("BotTickSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type BotTickSchedule = __Infer<typeof BotTickSchedule>;
+
+export const CellMove = __t.object("CellMove", {
+  cellId: __t.u64(),
+  x: __t.f32(),
+  y: __t.f32(),
+});
+export type CellMove = __Infer<typeof CellMove>;
+
+export const ChatCleanupSchedule = __t.object("ChatCleanupSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type ChatCleanupSchedule = __Infer<typeof ChatCleanupSchedule>;
+
+export const ChatMessage = __t.object("ChatMessage", {
+  id: __t.u64(),
+  senderIdentity: __t.identity(),
+  senderName: __t.string(),
+  text: __t.string(),
+  timestamp: __t.timestamp(),
+});
+export type ChatMessage = __Infer<typeof ChatMessage>;
+
+export const CollisionSchedule = __t.object("CollisionSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type CollisionSchedule = __Infer<typeof CollisionSchedule>;
+
+export const ColorPalette = __t.object("ColorPalette", {
+  color: __t.u32(),
+});
+export type ColorPalette = __Infer<typeof ColorPalette>;
+
+export const DisconnectSchedule = __t.object("DisconnectSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+  playerIdentity: __t.identity(),
+});
+export type DisconnectSchedule = __Infer<typeof DisconnectSchedule>;
+
+export const EatOverlapMode = __t.enum("EatOverlapMode", {
+  EdgeTouch: __t.unit(),
+  CenterCover: __t.unit(),
+});
+export type EatOverlapMode = __Infer<typeof EatOverlapMode>;
+
 export const EjectedMass = __t.object("EjectedMass", {
   id: __t.u64(),
   x: __t.f32(),
   y: __t.f32(),
   radius: __t.f32(),
   mass: __t.f32(),
+  ejectedAt: __t.timestamp(),
+  vx: __t.f32(),
+  vy: __t.f32(),
+  color: __t.u32(),
+  ejector: __t.identity(),
 });
 export type EjectedMass = __Infer<typeof EjectedMass>;
 
+export const EjectedMassExpirySchedule = __t.object("EjectedMassExpirySchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type EjectedMassExpirySchedule = __Infer<typeof EjectedMassExpirySchedule>;
+
+export const EjectedMotionSchedule = __t.object("EjectedMotionSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type EjectedMotionSchedule = __Infer<typeof EjectedMotionSchedule>;
+
+export const FoodCluster = __t.object("FoodCluster", {
+  id: __t.u64(),
+  x: __t.f32(),
+  y: __t.f32(),
+});
+export type FoodCluster = __Infer<typeof FoodCluster>;
+
+export const FoodDistribution = __t.enum("FoodDistribution", {
+  Uniform: __t.unit(),
+  Clustered: __t.unit(),
+});
+export type FoodDistribution = __Infer<typeof FoodDistribution>;
+
+export const FoodMagnetSchedule = __t.object("FoodMagnetSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type FoodMagnetSchedule = __Infer<typeof FoodMagnetSchedule>;
+
+export const FoodMaintainSchedule = __t.object("FoodMaintainSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type FoodMaintainSchedule = __Infer<typeof FoodMaintainSchedule>;
+
 export const FoodPellet = __t.object("FoodPellet", {
   id: __t.u64(),
   x: __t.f32(),
   y: __t.f32(),
   radius: __t.f32(),
+  mass: __t.f32(),
 });
 export type FoodPellet = __Infer<typeof FoodPellet>;
 
+export const FoodReplacement = __t.enum("FoodReplacement", {
+  TopUp: __t.unit(),
+  Immediate: __t.unit(),
+  Burst: __t.unit(),
+});
+export type FoodReplacement = __Infer<typeof FoodReplacement>;
+
 export const GameConfig = __t.object("GameConfig", {
   id: __t.u32(),
   maxFood: __t.u32(),
   worldWidth: __t.u32(),
   worldHeight: __t.u32(),
+  maxMass: __t.f32(),
+  botCount: __t.u32(),
+  maxPlayers: __t.u32(),
+  get worldShape() {
+    return WorldShape;
+  },
+  decayMassExponent: __t.f32(),
+  decayIntervalMicros: __t.i64(),
+  decayRate: __t.f32(),
+  ejectDistance: __t.f32(),
+  ejectSpread: __t.f32(),
+  autoResize: __t.bool(),
+  idleTimeoutMicros: __t.i64(),
+  hardcore: __t.bool(),
+  foodMagnet: __t.bool(),
+  paused: __t.bool(),
+  get foodDistribution() {
+    return FoodDistribution;
+  },
+  get foodReplacement() {
+    return FoodReplacement;
+  },
+  initialMass: __t.f32(),
+  minSplitMass: __t.f32(),
+  mergeDelayMicros: __t.i64(),
+  mergeDelayPerMassMicros: __t.f32(),
+  minCellMass: __t.f32(),
+  ejectMassAmount: __t.f32(),
+  radiusScale: __t.f32(),
+  radiusExponent: __t.f32(),
+  foodRadius: __t.f32(),
+  absorbRatio: __t.f32(),
+  scatterLostMass: __t.bool(),
+  get eatOverlapMode() {
+    return EatOverlapMode;
+  },
+  eatCoverFactor: __t.f32(),
+  rngSeed: __t.option(__t.u64()),
 });
 export type GameConfig = __Infer<typeof GameConfig>;
 
+export const GameEvent = __t.object("GameEvent", {
+  id: __t.u64(),
+  get kind() {
+    return GameEventKind;
+  },
+  identity: __t.option(__t.identity()),
+  payload: __t.string(),
+  timestamp: __t.timestamp(),
+});
+export type GameEvent = __Infer<typeof GameEvent>;
+
+export const GameEventCleanupSchedule = __t.object("GameEventCleanupSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type GameEventCleanupSchedule = __Infer<typeof GameEventCleanupSchedule>;
+
+export const GameEventKind = __t.enum("GameEventKind", {
+  Spawn: __t.unit(),
+  Death: __t.unit(),
+  Split: __t.unit(),
+  Eject: __t.unit(),
+  RoundEnd: __t.unit(),
+});
+export type GameEventKind = __Infer<typeof GameEventKind>;
+
+export const GameRound = __t.object("GameRound", {
+  roundId: __t.u64(),
+  startedAt: __t.timestamp(),
+  endsAt: __t.timestamp(),
+  winnerIdentity: __t.option(__t.identity()),
+  winnerName: __t.option(__t.string()),
+});
+export type GameRound = __Infer<typeof GameRound>;
+
+export const GameSnapshot = __t.object("GameSnapshot", {
+  id: __t.u32(),
+  takenAt: __t.timestamp(),
+  playerCount: __t.u32(),
+  cellCount: __t.u32(),
+  totalMass: __t.f32(),
+  foodCount: __t.u32(),
+  ejectedCount: __t.u32(),
+  virusCount: __t.u32(),
+});
+export type GameSnapshot = __Infer<typeof GameSnapshot>;
+
+export const Graveyard = __t.object("Graveyard", {
+  identity: __t.identity(),
+  name: __t.string(),
+  diedAt: __t.timestamp(),
+});
+export type Graveyard = __Infer<typeof Graveyard>;
+
+export const GridCell = __t.object("GridCell", {
+  id: __t.u64(),
+  bucket: __t.u64(),
+  get entity() {
+    return GridEntity;
+  },
+});
+export type GridCell = __Infer<typeof GridCell>;
+
+export const GridEntity = __t.enum("GridEntity", {
+  Player: __t.identity(),
+  Food: __t.u64(),
+  Cell: __t.u64(),
+  EjectedMass: __t.u64(),
+  Virus: __t.u64(),
+});
+export type GridEntity = __Infer<typeof GridEntity>;
+
+export const IdleReapSchedule = __t.object("IdleReapSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type IdleReapSchedule = __Infer<typeof IdleReapSchedule>;
+
+export const KillEvent = __t.object("KillEvent", {
+  id: __t.u64(),
+  killerIdentity: __t.identity(),
+  killerName: __t.string(),
+  victimName: __t.string(),
+  timestamp: __t.timestamp(),
+});
+export type KillEvent = __Infer<typeof KillEvent>;
+
+export const KillEventCleanupSchedule = __t.object("KillEventCleanupSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type KillEventCleanupSchedule = __Infer<typeof KillEventCleanupSchedule>;
+
+export const LastLoadout = __t.object("LastLoadout", {
+  identity: __t.identity(),
+  name: __t.string(),
+  color: __t.u32(),
+});
+export type LastLoadout = __Infer<typeof LastLoadout>;
+
+export const LeaderboardEntry = __t.object("LeaderboardEntry", {
+  rank: __t.u32(),
+  identity: __t.identity(),
+  name: __t.string(),
+  mass: __t.f32(),
+  teamId: __t.option(__t.u32()),
+});
+export type LeaderboardEntry = __Infer<typeof LeaderboardEntry>;
+
+export const MassAudit = __t.object("MassAudit", {
+  id: __t.u64(),
+  auditedAt: __t.timestamp(),
+  playerMass: __t.f32(),
+  cellMass: __t.f32(),
+  foodMass: __t.f32(),
+  ejectedMass: __t.f32(),
+  total: __t.f32(),
+  delta: __t.f32(),
+});
+export type MassAudit = __Infer<typeof MassAudit>;
+
+export const MassAuditSchedule = __t.object("MassAuditSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type MassAuditSchedule = __Infer<typeof MassAuditSchedule>;
+
 export const MassDecaySchedule = __t.object("MassDecaySchedule", {
   scheduledId: __t.u64(),
   scheduledAt: __t.scheduleAt(),
 });
 export type MassDecaySchedule = __Infer<typeof MassDecaySchedule>;
 
+export const MetricsSchedule = __t.object("MetricsSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type MetricsSchedule = __Infer<typeof MetricsSchedule>;
+
+export const MinimapBlip = __t.object("MinimapBlip", {
+  identity: __t.identity(),
+  gridX: __t.u16(),
+  gridY: __t.u16(),
+  massBucket: __t.u8(),
+});
+export type MinimapBlip = __Infer<typeof MinimapBlip>;
+
+export const MinimapSchedule = __t.object("MinimapSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type MinimapSchedule = __Infer<typeof MinimapSchedule>;
+
+export const Obstacle = __t.object("Obstacle", {
+  id: __t.u64(),
+  x: __t.f32(),
+  y: __t.f32(),
+  width: __t.f32(),
+  height: __t.f32(),
+});
+export type Obstacle = __Infer<typeof Obstacle>;
+
 export const Player = __t.object("Player", {
   identity: __t.identity(),
   name: __t.string(),
@@ -49,6 +379,21 @@ export const Player = __t.object("Player", {
   radius: __t.f32(),
   mass: __t.f32(),
   color: __t.u32(),
+  lastMoveMicros: __t.i64(),
+  spawnedAt: __t.timestamp(),
+  spawnProtectedUntil: __t.timestamp(),
+  invulnerable: __t.bool(),
+  teamId: __t.option(__t.u32()),
+  disconnectedAt: __t.option(__t.timestamp()),
+  onlineSince: __t.timestamp(),
+  lastActiveAt: __t.timestamp(),
+  boostUntil: __t.timestamp(),
+  shieldUntil: __t.timestamp(),
+  teleportReadyAt: __t.timestamp(),
+  score: __t.u64(),
+  viewRadius: __t.f32(),
+  vx: __t.f32(),
+  vy: __t.f32(),
 });
 export type Player = __Infer<typeof Player>;
 
@@ -59,9 +404,106 @@ export const PlayerCell = __t.object("PlayerCell", {
   y: __t.f32(),
   radius: __t.f32(),
   mass: __t.f32(),
+  lastMoveMicros: __t.i64(),
+  mergeAt: __t.timestamp(),
+  vx: __t.f32(),
+  vy: __t.f32(),
+  color: __t.u32(),
 });
 export type PlayerCell = __Infer<typeof PlayerCell>;
 
+export const PlayerStats = __t.object("PlayerStats", {
+  identity: __t.identity(),
+  foodEaten: __t.u64(),
+  playersEaten: __t.u64(),
+  timesSplit: __t.u64(),
+  massEjected: __t.f32(),
+  maxMassReached: __t.f32(),
+  totalPlaytimeMicros: __t.u64(),
+  deaths: __t.u64(),
+});
+export type PlayerStats = __Infer<typeof PlayerStats>;
+
+export const Portal = __t.object("Portal", {
+  id: __t.u64(),
+  entryX: __t.f32(),
+  entryY: __t.f32(),
+  exitX: __t.f32(),
+  exitY: __t.f32(),
+  radius: __t.f32(),
+});
+export type Portal = __Infer<typeof Portal>;
+
+export const PortalSchedule = __t.object("PortalSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type PortalSchedule = __Infer<typeof PortalSchedule>;
+
+export const PowerUp = __t.object("PowerUp", {
+  id: __t.u64(),
+  x: __t.f32(),
+  y: __t.f32(),
+  get kind() {
+    return PowerUpKind;
+  },
+});
+export type PowerUp = __Infer<typeof PowerUp>;
+
+export const PowerUpKind = __t.enum("PowerUpKind", {
+  SpeedBoost: __t.unit(),
+  Shield: __t.unit(),
+});
+export type PowerUpKind = __Infer<typeof PowerUpKind>;
+
+export const PowerUpSpawnSchedule = __t.object("PowerUpSpawnSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type PowerUpSpawnSchedule = __Infer<typeof PowerUpSpawnSchedule>;
+
+export const RateLimit = __t.object("RateLimit", {
+  identity: __t.identity(),
+  lastEjectMicros: __t.i64(),
+  lastSplitMicros: __t.i64(),
+});
+export type RateLimit = __Infer<typeof RateLimit>;
+
+export const RngState = __t.object("RngState", {
+  id: __t.u32(),
+  draws: __t.u64(),
+});
+export type RngState = __Infer<typeof RngState>;
+
+export const RoundEndSchedule = __t.object("RoundEndSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+  roundId: __t.u64(),
+});
+export type RoundEndSchedule = __Infer<typeof RoundEndSchedule>;
+
+export const SafeZone = __t.object("SafeZone", {
+  id: __t.u32(),
+  centerX: __t.f32(),
+  centerY: __t.f32(),
+  radius: __t.f32(),
+  shrinkRate: __t.f32(),
+});
+export type SafeZone = __Infer<typeof SafeZone>;
+
+export const SpawnProtectionSchedule = __t.object("SpawnProtectionSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+  playerIdentity: __t.identity(),
+});
+export type SpawnProtectionSchedule = __Infer<typeof SpawnProtectionSchedule>;
+
+export const Spectator = __t.object("Spectator", {
+  identity: __t.identity(),
+  following: __t.option(__t.identity()),
+});
+export type Spectator = __Infer<typeof Spectator>;
+
 export const SplitMergeSchedule = __t.object("SplitMergeSchedule", {
   scheduledId: __t.u64(),
   scheduledAt: __t.scheduleAt(),
@@ -69,3 +511,88 @@ export const SplitMergeSchedule = __t.object("SplitMergeSchedule", {
 });
 export type SplitMergeSchedule = __Infer<typeof SplitMergeSchedule>;
 
+export const SplitMotionSchedule = __t.object("SplitMotionSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type SplitMotionSchedule = __Infer<typeof SplitMotionSchedule>;
+
+export const TeamScore = __t.object("TeamScore", {
+  teamId: __t.u32(),
+  totalMass: __t.f32(),
+  memberCount: __t.u32(),
+});
+export type TeamScore = __Infer<typeof TeamScore>;
+
+export const Threat = __t.object("Threat", {
+  id: __t.u64(),
+  viewer: __t.identity(),
+  threatIdentity: __t.identity(),
+  threatCellId: __t.u64(),
+  mass: __t.f32(),
+  distance: __t.f32(),
+});
+export type Threat = __Infer<typeof Threat>;
+
+export const ViewEntity = __t.object("ViewEntity", {
+  id: __t.u64(),
+  viewer: __t.identity(),
+  get kind() {
+    return ViewEntityKind;
+  },
+  entityId: __t.u64(),
+  owner: __t.option(__t.identity()),
+  x: __t.f32(),
+  y: __t.f32(),
+  radius: __t.f32(),
+});
+export type ViewEntity = __Infer<typeof ViewEntity>;
+
+export const ViewEntityKind = __t.enum("ViewEntityKind", {
+  Player: __t.unit(),
+  Cell: __t.unit(),
+  Food: __t.unit(),
+  EjectedMass: __t.unit(),
+});
+export type ViewEntityKind = __Infer<typeof ViewEntityKind>;
+
+export const Virus = __t.object("Virus", {
+  id: __t.u64(),
+  x: __t.f32(),
+  y: __t.f32(),
+  radius: __t.f32(),
+  mass: __t.f32(),
+  feedCount: __t.u32(),
+});
+export type Virus = __Infer<typeof Virus>;
+
+export const VirusSpawnSchedule = __t.object("VirusSpawnSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type VirusSpawnSchedule = __Infer<typeof VirusSpawnSchedule>;
+
+export const WorldResizeSchedule = __t.object("WorldResizeSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type WorldResizeSchedule = __Infer<typeof WorldResizeSchedule>;
+
+export const WorldShape = __t.enum("WorldShape", {
+  Rectangle: __t.unit(),
+  Circle: __t.unit(),
+});
+export type WorldShape = __Infer<typeof WorldShape>;
+
+export const ZoneDamageSchedule = __t.object("ZoneDamageSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type ZoneDamageSchedule = __Infer<typeof ZoneDamageSchedule>;
+
+export const ZoneShrinkSchedule = __t.object("ZoneShrinkSchedule", {
+  scheduledId: __t.u64(),
+  scheduledAt: __t.scheduleAt(),
+});
+export type ZoneShrinkSchedule = __Infer<typeof ZoneShrinkSchedule>;
+
@@ -6,27 +6,139 @@
 import { type Infer as __Infer } from "spacetimedb";
 
 // Import all reducer arg schemas
+import AddObstacleReducer from "../add_obstacle_reducer";
+import AddPaletteColorReducer from "../add_palette_color_reducer";
+import AddPortalReducer from "../add_portal_reducer";
+import AttemptMergeReducer from "../attempt_merge_reducer";
+import BanPlayerReducer from "../ban_player_reducer";
+import BecomeSpectatorReducer from "../become_spectator_reducer";
+import ClearSafeZoneReducer from "../clear_safe_zone_reducer";
 import DespawnPlayerReducer from "../despawn_player_reducer";
+import EatCellReducer from "../eat_cell_reducer";
 import EatEjectedMassReducer from "../eat_ejected_mass_reducer";
+import EatEjectedMassCellReducer from "../eat_ejected_mass_cell_reducer";
 import EatFoodReducer from "../eat_food_reducer";
 import EatFoodCellReducer from "../eat_food_cell_reducer";
+import EatNearbyFoodReducer from "../eat_nearby_food_reducer";
 import EatPlayerReducer from "../eat_player_reducer";
 import EatPlayerCellReducer from "../eat_player_cell_reducer";
+import EatPowerupReducer from "../eat_powerup_reducer";
+import EatVirusReducer from "../eat_virus_reducer";
 import EjectMassReducer from "../eject_mass_reducer";
+import ExportStateReducer from "../export_state_reducer";
+import FeedVirusReducer from "../feed_virus_reducer";
+import FollowPlayerReducer from "../follow_player_reducer";
+import KickPlayerReducer from "../kick_player_reducer";
+import LeaveSpectatorReducer from "../leave_spectator_reducer";
+import PauseGameReducer from "../pause_game_reducer";
+import RefreshThreatsReducer from "../refresh_threats_reducer";
+import RefreshViewReducer from "../refresh_view_reducer";
+import RemoveObstacleReducer from "../remove_obstacle_reducer";
+import RemovePaletteColorReducer from "../remove_palette_color_reducer";
+import RemovePortalReducer from "../remove_portal_reducer";
+import ResetArenaReducer from "../reset_arena_reducer";
+import ResetStatsReducer from "../reset_stats_reducer";
+import RespawnReducer from "../respawn_reducer";
+import ResumeGameReducer from "../resume_game_reducer";
+import SendChatReducer from "../send_chat_reducer";
+import SetAbsorbRuleReducer from "../set_absorb_rule_reducer";
+import SetAutoResizeReducer from "../set_auto_resize_reducer";
+import SetBotCountReducer from "../set_bot_count_reducer";
+import SetConfigReducer from "../set_config_reducer";
+import SetDecayReducer from "../set_decay_reducer";
+import SetDecayExponentReducer from "../set_decay_exponent_reducer";
+import SetEatOverlapReducer from "../set_eat_overlap_reducer";
+import SetEjectDistanceReducer from "../set_eject_distance_reducer";
+import SetEjectSpreadReducer from "../set_eject_spread_reducer";
+import SetFoodDistributionReducer from "../set_food_distribution_reducer";
+import SetFoodMagnetReducer from "../set_food_magnet_reducer";
+import SetFoodReplacementReducer from "../set_food_replacement_reducer";
+import SetHardcoreReducer from "../set_hardcore_reducer";
+import SetIdleTimeoutReducer from "../set_idle_timeout_reducer";
+import SetMassRulesReducer from "../set_mass_rules_reducer";
+import SetMaxMassReducer from "../set_max_mass_reducer";
+import SetMaxPlayersReducer from "../set_max_players_reducer";
+import SetMergeDelayReducer from "../set_merge_delay_reducer";
+import SetMinCellMassReducer from "../set_min_cell_mass_reducer";
+import SetRngSeedReducer from "../set_rng_seed_reducer";
+import SetSizeCurveReducer from "../set_size_curve_reducer";
+import SetWorldShapeReducer from "../set_world_shape_reducer";
 import SpawnPlayerReducer from "../spawn_player_reducer";
+import SpawnPlayerTeamReducer from "../spawn_player_team_reducer";
 import SplitCellReducer from "../split_cell_reducer";
+import StartRoundReducer from "../start_round_reducer";
+import StartSafeZoneReducer from "../start_safe_zone_reducer";
+import TransferMassReducer from "../transfer_mass_reducer";
+import UnbanPlayerReducer from "../unban_player_reducer";
+import UpdateAllPositionsReducer from "../update_all_positions_reducer";
 import UpdateCellPositionReducer from "../update_cell_position_reducer";
 import UpdatePositionReducer from "../update_position_reducer";
 
+export type AddObstacleParams = __Infer<typeof AddObstacleReducer>;
+export type AddPaletteColorParams = __Infer<typeof AddPaletteColorReducer>;
+export type AddPortalParams = __Infer<typeof AddPortalReducer>;
+export type AttemptMergeParams = __Infer<typeof AttemptMergeReducer>;
+export type BanPlayerParams = __Infer<typeof BanPlayerReducer>;
+export type BecomeSpectatorParams = __Infer<typeof BecomeSpectatorReducer>;
+export type ClearSafeZoneParams = __Infer<typeof ClearSafeZoneReducer>;
 export type DespawnPlayerParams = __Infer<typeof DespawnPlayerReducer>;
+export type EatCellParams = __Infer<typeof EatCellReducer>;
 export type EatEjectedMassParams = __Infer<typeof EatEjectedMassReducer>;
+export type EatEjectedMassCellParams = __Infer<typeof EatEjectedMassCellReducer>;
 export type EatFoodParams = __Infer<typeof EatFoodReducer>;
 export type EatFoodCellParams = __Infer<typeof EatFoodCellReducer>;
+export type EatNearbyFoodParams = __Infer<typeof EatNearbyFoodReducer>;
 export type EatPlayerParams = __Infer<typeof EatPlayerReducer>;
 export type EatPlayerCellParams = __Infer<typeof EatPlayerCellReducer>;
+export type EatPowerupParams = __Infer<typeof EatPowerupReducer>;
+export type EatVirusParams = __Infer<typeof EatVirusReducer>;
 export type EjectMassParams = __Infer<typeof EjectMassReducer>;
+export type ExportStateParams = __Infer<typeof ExportStateReducer>;
+export type FeedVirusParams = __Infer<typeof FeedVirusReducer>;
+export type FollowPlayerParams = __Infer<typeof FollowPlayerReducer>;
+export type KickPlayerParams = __Infer<typeof KickPlayerReducer>;
+export type LeaveSpectatorParams = __Infer<typeof LeaveSpectatorReducer>;
+export type PauseGameParams = __Infer<typeof PauseGameReducer>;
+export type RefreshThreatsParams = __Infer<typeof RefreshThreatsReducer>;
+export type RefreshViewParams = __Infer<typeof RefreshViewReducer>;
+export type RemoveObstacleParams = __Infer<typeof RemoveObstacleReducer>;
+export type RemovePaletteColorParams = __Infer<typeof RemovePaletteColorReducer>;
+export type RemovePortalParams = __Infer<typeof RemovePortalReducer>;
+export type ResetArenaParams = __Infer<typeof ResetArenaReducer>;
+export type ResetStatsParams = __Infer<typeof ResetStatsReducer>;
+export type RespawnParams = __Infer<typeof RespawnReducer>;
+export type ResumeGameParams = __Infer<typeof ResumeGameReducer>;
+export type SendChatParams = __Infer<typeof SendChatReducer>;
+export type SetAbsorbRuleParams = __Infer<typeof SetAbsorbRuleReducer>;
+export type SetAutoResizeParams = __Infer<typeof SetAutoResizeReducer>;
+export type SetBotCountParams = __Infer<typeof SetBotCountReducer>;
+export type SetConfigParams = __Infer<typeof SetConfigReducer>;
+export type SetDecayParams = __Infer<typeof SetDecayReducer>;
+export type SetDecayExponentParams = __Infer<typeof SetDecayExponentReducer>;
+export type SetEatOverlapParams = __Infer<typeof SetEatOverlapReducer>;
+export type SetEjectDistanceParams = __Infer<typeof SetEjectDistanceReducer>;
+export type SetEjectSpreadParams = __Infer<typeof SetEjectSpreadReducer>;
+export type SetFoodDistributionParams = __Infer<typeof SetFoodDistributionReducer>;
+export type SetFoodMagnetParams = __Infer<typeof SetFoodMagnetReducer>;
+export type SetFoodReplacementParams = __Infer<typeof SetFoodReplacementReducer>;
+export type SetHardcoreParams = __Infer<typeof SetHardcoreReducer>;
+export type SetIdleTimeoutParams = __Infer<typeof SetIdleTimeoutReducer>;
+export type SetMassRulesParams = __Infer<typeof SetMassRulesReducer>;
+export type SetMaxMassParams = __Infer<typeof SetMaxMassReducer>;
+export type SetMaxPlayersParams = __Infer<typeof SetMaxPlayersReducer>;
+export type SetMergeDelayParams = __Infer<typeof SetMergeDelayReducer>;
+export type SetMinCellMassParams = __Infer<typeof SetMinCellMassReducer>;
+export type SetRngSeedParams = __Infer<typeof SetRngSeedReducer>;
+export type SetSizeCurveParams = __Infer<typeof SetSizeCurveReducer>;
+export type SetWorldShapeParams = __Infer<typeof SetWorldShapeReducer>;
 export type SpawnPlayerParams = __Infer<typeof SpawnPlayerReducer>;
+export type SpawnPlayerTeamParams = __Infer<typeof SpawnPlayerTeamReducer>;
 export type SplitCellParams = __Infer<typeof SplitCellReducer>;
+export type StartRoundParams = __Infer<typeof StartRoundReducer>;
+export type StartSafeZoneParams = __Infer<typeof StartSafeZoneReducer>;
+export type TransferMassParams = __Infer<typeof TransferMassReducer>;
+export type UnbanPlayerParams = __Infer<typeof UnbanPlayerReducer>;
+export type UpdateAllPositionsParams = __Infer<typeof UpdateAllPositionsReducer>;
 export type UpdateCellPositionParams = __Infer<typeof UpdateCellPositionReducer>;
 export type UpdatePositionParams = __Infer<typeof UpdatePositionReducer>;
 